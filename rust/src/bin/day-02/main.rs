@@ -1,39 +1,33 @@
 use std::fs;
 
-use intcode::intcode_computer::IntcodeComputer;
+use intcode::{computer_builder::ComputerBuilder, intcode_computer::Dialect};
 
-#[tokio::main]
-async fn main() {
+fn main() {
     let part_1_result = part_1(
         &fs::read_to_string("src/bin/day-02/input.txt").unwrap(),
         true,
-    )
-    .await;
+    );
     println!("Part 1 result: {}", part_1_result);
-    let part_2_result = part_2(&fs::read_to_string("src/bin/day-02/input.txt").unwrap()).await;
+    let part_2_result = part_2(&fs::read_to_string("src/bin/day-02/input.txt").unwrap());
     println!("Part 2 result: {}", part_2_result);
 }
 
-async fn part_1(input: &str, modify: bool) -> i64 {
-    let mut computer = IntcodeComputer::new(input);
+fn part_1(input: &str, modify: bool) -> i64 {
+    let mut builder = ComputerBuilder::parse(input).dialect(Dialect::Day02);
     if modify {
-        computer.set_memory(1, 12);
-        computer.set_memory(2, 2);
+        builder = builder.patch(1, 12).patch(2, 2);
     }
-    computer.run().await;
+    let mut computer = builder.build();
+    computer.run_blocking().unwrap();
     computer.get_memory(0)
 }
 
-async fn part_2(input: &str) -> i64 {
-    let mut computer = IntcodeComputer::new(input);
-    let memory_snapshot = computer.get_memory_snapshot();
+fn part_2(input: &str) -> i64 {
+    let builder = ComputerBuilder::parse(input).dialect(Dialect::Day02);
     for noun in 0..100 {
         for verb in 0..100 {
-            computer.restore_memory_snapshot(memory_snapshot.clone());
-            computer.reset_instruction_pointer();
-            computer.set_memory(1, noun);
-            computer.set_memory(2, verb);
-            computer.run().await;
+            let mut computer = builder.clone().patch(1, noun).patch(2, verb).build();
+            computer.run_blocking().unwrap();
             if computer.get_memory(0) == 19690720 {
                 return 100 * noun + verb;
             }
@@ -46,28 +40,27 @@ async fn part_2(input: &str) -> i64 {
 mod tests {
     use super::*;
 
-    #[tokio::test]
-    async fn test_part_1() {
-        assert_eq!(part_1("1,9,10,3,2,3,11,0,99,30,40,50", false).await, 3500);
-        assert_eq!(part_1("1,0,0,0,99", false).await, 2);
-        assert_eq!(part_1("2,3,0,3,99", false).await, 2);
-        assert_eq!(part_1("2,4,4,5,99,0", false).await, 2);
-        assert_eq!(part_1("1,1,1,4,99,5,6,0,99", false).await, 30);
+    #[test]
+    fn test_part_1() {
+        assert_eq!(part_1("1,9,10,3,2,3,11,0,99,30,40,50", false), 3500);
+        assert_eq!(part_1("1,0,0,0,99", false), 2);
+        assert_eq!(part_1("2,3,0,3,99", false), 2);
+        assert_eq!(part_1("2,4,4,5,99,0", false), 2);
+        assert_eq!(part_1("1,1,1,4,99,5,6,0,99", false), 30);
     }
 
-    #[tokio::test]
-    async fn test_part_1_actual_input() {
+    #[test]
+    fn test_part_1_actual_input() {
         let result = part_1(
             &fs::read_to_string("src/bin/day-02/input.txt").unwrap(),
             true,
-        )
-        .await;
+        );
         assert_eq!(result, 2890696);
     }
 
-    #[tokio::test]
-    async fn test_part_2_actual_input() {
-        let result = part_2(&fs::read_to_string("src/bin/day-02/input.txt").unwrap()).await;
+    #[test]
+    fn test_part_2_actual_input() {
+        let result = part_2(&fs::read_to_string("src/bin/day-02/input.txt").unwrap());
         assert_eq!(result, 8226);
     }
 }
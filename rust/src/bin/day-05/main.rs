@@ -3,6 +3,7 @@ use std::fs;
 use intcode::{
     computer_io::{StdInComputerInput, StdOutComputerOutput},
     intcode_computer::{run_program, IntcodeComputer},
+    server::IntcodeServer,
 };
 
 #[tokio::main]
@@ -15,20 +16,23 @@ async fn main() {
 
 // Demonstrates the use of the IntcodeComputer with standard I/O
 async fn _part_1_stdio(input: &str) -> i64 {
-    let (stdin, computer_input) = StdInComputerInput::new();
     let (mut stdout, computer_output) = StdOutComputerOutput::new();
-    let mut computer = IntcodeComputer::new_with_io(input, computer_input, computer_output);
+    let mut computer =
+        IntcodeComputer::new_with_io(input, StdInComputerInput::new(), computer_output);
 
-    let stdin_future = tokio::spawn(async move { stdin.run().await });
     let stdout_future = tokio::spawn(async move { stdout.run().await });
 
-    computer.run().await;
+    computer.run().await.unwrap();
 
-    stdin_future.abort();
     stdout_future.abort();
     0
 }
 
+// Runs the program for every client that connects, e.g. with `nc localhost 4005`
+async fn _part_1_tcp(input: &str) {
+    IntcodeServer::new(input).listen(4005).await.unwrap();
+}
+
 async fn part_1(input: &str) -> i64 {
     let output = run_program(input, "1").await;
     get_diagnostic_code(&output)
@@ -45,8 +49,6 @@ fn get_diagnostic_code(result: &str) -> i64 {
 
 #[cfg(test)]
 mod tests {
-    use intcode::intcode_computer::run_program;
-
     use super::*;
 
     #[tokio::test]
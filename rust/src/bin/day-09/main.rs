@@ -20,7 +20,7 @@ async fn part_2(input: &str) -> i64 {
 
 #[cfg(test)]
 mod tests {
-    use intcode::intcode_computer::run_program;
+    use intcode::intcode_computer::{run_program, run_program_values};
 
     use super::*;
 
@@ -39,8 +39,11 @@ mod tests {
     #[tokio::test]
     async fn test_1() {
         let program = "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99";
-        let result = run_program(program, "").await;
-        let exptected = program.replace(",", "");
+        let result = run_program_values(program, "").await;
+        let exptected = program
+            .split(',')
+            .map(|value| value.parse::<i64>().unwrap())
+            .collect::<Vec<_>>();
         assert_eq!(result, exptected);
     }
 
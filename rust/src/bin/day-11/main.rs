@@ -1,6 +1,10 @@
-use std::{collections::HashMap, fs};
+use std::{
+    collections::HashMap,
+    fs,
+    sync::{Arc, Mutex},
+};
 
-use intcode::intcode_computer::IntcodeComputer;
+use intcode::{intcode_computer::IntcodeComputer, intcode_io::OnDemandInput, replay::Recording};
 
 #[tokio::main]
 async fn main() {
@@ -22,29 +26,67 @@ async fn part_2(input: &str) -> String {
 }
 
 async fn run_robot(input: &str, start_color: i64) -> HashMap<(i64, i64), i64> {
-    let mut map: HashMap<(i64, i64), i64> = HashMap::new();
-    let mut position = (0, 0);
-    let mut direction = Direction::Up;
-    let (sender, computer_input) = tokio::sync::mpsc::channel(1);
-    let (computer_output, mut receiver) = tokio::sync::mpsc::channel(1);
+    run_robot_recorded(input, start_color).await.0
+}
+
+async fn run_robot_recorded(
+    input: &str,
+    start_color: i64,
+) -> (HashMap<(i64, i64), i64>, Recording) {
+    let robot = Arc::new(Mutex::new(Robot::new(start_color)));
+    let camera_robot = robot.clone();
+    let (computer_input, computer_output) = OnDemandInput::new(move |outputs: &[i64]| {
+        let mut robot = camera_robot.lock().unwrap();
+        robot.process(outputs);
+        Some(robot.current_color())
+    });
+    let outputs = computer_output.clone();
     let mut computer = IntcodeComputer::new_with_io(input, computer_input, computer_output);
-    tokio::spawn(async move { computer.run().await });
-    sender.send(start_color).await.unwrap();
-    loop {
-        let color = match receiver.recv().await {
-            Some(color) => color,
-            None => break,
-        };
-        let turn = match receiver.recv().await {
-            Some(turn) => turn,
-            None => break,
-        };
-        map.insert(position, color);
-        direction = direction.turn(turn);
-        position = direction.step(position);
-        let _ = sender.send(*map.get(&position).unwrap_or(&0)).await;
+    let recorder = computer.record_io();
+    computer.run().await.unwrap();
+    let mut robot = robot.lock().unwrap();
+    robot.process(&outputs.lock().unwrap());
+    (std::mem::take(&mut robot.map), recorder.recording())
+}
+
+struct Robot {
+    map: HashMap<(i64, i64), i64>,
+    position: (i64, i64),
+    direction: Direction,
+    start_color: i64,
+    processed: usize,
+}
+
+impl Robot {
+    fn new(start_color: i64) -> Self {
+        Robot {
+            map: HashMap::new(),
+            position: (0, 0),
+            direction: Direction::Up,
+            start_color,
+            processed: 0,
+        }
+    }
+
+    // Applies the (color, turn) pairs that have been output since the last call
+    fn process(&mut self, outputs: &[i64]) {
+        while self.processed + 1 < outputs.len() {
+            let color = outputs[self.processed];
+            let turn = outputs[self.processed + 1];
+            self.map.insert(self.position, color);
+            self.direction = self.direction.turn(turn);
+            self.position = self.direction.step(self.position);
+            self.processed += 2;
+        }
+    }
+
+    fn current_color(&self) -> i64 {
+        if self.processed == 0 {
+            self.start_color
+        } else {
+            *self.map.get(&self.position).unwrap_or(&0)
+        }
     }
-    map
 }
 
 enum Direction {
@@ -115,8 +157,24 @@ fn map_to_string(map: &HashMap<(i64, i64), i64>) -> String {
 
 #[cfg(test)]
 mod tests {
+    use intcode::replay::verify_replay;
+
     use super::*;
 
+    #[tokio::test]
+    async fn test_replay_robot_session() {
+        let input = fs::read_to_string("src/bin/day-11/input.txt").unwrap();
+        let (_, recording) = run_robot_recorded(&input, 1).await;
+        let path = std::env::temp_dir().join(format!("day-11-robot-{}.replay", std::process::id()));
+        recording.save(&path).unwrap();
+
+        let loaded = Recording::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded, recording);
+        assert_eq!(verify_replay(&input, &loaded), Ok(()));
+    }
+
     #[tokio::test]
     async fn test_part_1_actual_input() {
         let result = part_1(&fs::read_to_string("src/bin/day-11/input.txt").unwrap()).await;
@@ -0,0 +1,152 @@
+use std::fmt;
+
+use crate::topology::{Topology, TopologyError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AmplifierMode {
+    Serial,
+    Feedback,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhaseScore {
+    pub phases: Vec<i64>,
+    pub output: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AmplifierError {
+    NoPhases,
+    Topology(TopologyError),
+    // The last amplifier halted without sending a signal
+    NoOutput,
+}
+
+impl From<TopologyError> for AmplifierError {
+    fn from(error: TopologyError) -> Self {
+        AmplifierError::Topology(error)
+    }
+}
+
+impl fmt::Display for AmplifierError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AmplifierError::NoPhases => write!(f, "No phase settings were given"),
+            AmplifierError::Topology(error) => write!(f, "{}", error),
+            AmplifierError::NoOutput => write!(f, "The last amplifier sent no signal"),
+        }
+    }
+}
+
+impl std::error::Error for AmplifierError {}
+
+// Runs one amplifier per phase setting, feeding 0 into the first one. In feedback mode the last
+// amplifier is wired back into the first and the result is the last signal sent around the loop.
+pub fn run_amplifiers(
+    program: &str,
+    phases: &[i64],
+    mode: AmplifierMode,
+) -> Result<i64, AmplifierError> {
+    if phases.is_empty() {
+        return Err(AmplifierError::NoPhases);
+    }
+    let mut topology = Topology::new();
+    let amplifiers = phases
+        .iter()
+        .enumerate()
+        .map(|(index, phase)| {
+            let initial_inputs = if index == 0 {
+                vec![*phase, 0]
+            } else {
+                vec![*phase]
+            };
+            topology.add_node(&amplifier_name(index), program, &initial_inputs)
+        })
+        .collect::<Vec<_>>();
+    for pair in amplifiers.windows(2) {
+        topology.connect(pair[0], pair[1]);
+    }
+    if mode == AmplifierMode::Feedback {
+        topology.connect(amplifiers[amplifiers.len() - 1], amplifiers[0]);
+    }
+
+    let result = topology.run_scheduled()?;
+    let last = amplifier_name(phases.len() - 1);
+    let output = match mode {
+        AmplifierMode::Serial => result.output(&last),
+        AmplifierMode::Feedback => result.edge_log(&last, &amplifier_name(0)),
+    };
+    output
+        .and_then(|output| output.last().copied())
+        .ok_or(AmplifierError::NoOutput)
+}
+
+// Returns None if there are no amplifiers or fewer phase settings than amplifiers
+pub fn optimise_phases(
+    program: &str,
+    amplifier_count: usize,
+    phase_set: &[i64],
+    mode: AmplifierMode,
+) -> Result<Option<PhaseScore>, AmplifierError> {
+    let mut best: Option<PhaseScore> = None;
+    for phases in phase_sequences(phase_set, amplifier_count) {
+        let output = run_amplifiers(program, &phases, mode)?;
+        if best.as_ref().is_none_or(|best| output > best.output) {
+            best = Some(PhaseScore { phases, output });
+        }
+    }
+    Ok(best)
+}
+
+pub fn score_all_phases(
+    program: &str,
+    amplifier_count: usize,
+    phase_set: &[i64],
+    mode: AmplifierMode,
+) -> Result<Vec<PhaseScore>, AmplifierError> {
+    let mut scores = Vec::new();
+    for phases in phase_sequences(phase_set, amplifier_count) {
+        let output = run_amplifiers(program, &phases, mode)?;
+        scores.push(PhaseScore { phases, output });
+    }
+    Ok(scores)
+}
+
+fn amplifier_name(index: usize) -> String {
+    format!("amplifier_{}", index)
+}
+
+// Every ordered selection of count distinct phase settings
+fn phase_sequences(phase_set: &[i64], count: usize) -> Vec<Vec<i64>> {
+    if count == 0 || count > phase_set.len() {
+        return Vec::new();
+    }
+    let mut sequences = Vec::new();
+    let mut used = vec![false; phase_set.len()];
+    let mut current = Vec::with_capacity(count);
+    collect_phase_sequences(phase_set, count, &mut used, &mut current, &mut sequences);
+    sequences
+}
+
+fn collect_phase_sequences(
+    phase_set: &[i64],
+    count: usize,
+    used: &mut [bool],
+    current: &mut Vec<i64>,
+    sequences: &mut Vec<Vec<i64>>,
+) {
+    if current.len() == count {
+        sequences.push(current.clone());
+        return;
+    }
+    for index in 0..phase_set.len() {
+        if used[index] {
+            continue;
+        }
+        used[index] = true;
+        current.push(phase_set[index]);
+        collect_phase_sequences(phase_set, count, used, current, sequences);
+        current.pop();
+        used[index] = false;
+    }
+}
@@ -0,0 +1,124 @@
+use std::task::{Context, Poll};
+
+use tokio::sync::mpsc::{
+    self,
+    error::{SendError, TryRecvError},
+    Receiver, Sender, UnboundedReceiver, UnboundedSender,
+};
+
+use crate::intcode_io::{AsyncIntcodeInput, AsyncIntcodeOutput, IoFuture};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelCapacity {
+    Bounded(usize),
+    Unbounded,
+}
+
+impl Default for ChannelCapacity {
+    fn default() -> Self {
+        ChannelCapacity::Bounded(1)
+    }
+}
+
+pub fn channel(capacity: ChannelCapacity) -> (ChannelSender, ChannelReceiver) {
+    match capacity {
+        ChannelCapacity::Bounded(capacity) => {
+            let (sender, receiver) = mpsc::channel(capacity);
+            (
+                ChannelSender::Bounded(sender),
+                ChannelReceiver::Bounded(receiver),
+            )
+        }
+        ChannelCapacity::Unbounded => {
+            let (sender, receiver) = mpsc::unbounded_channel();
+            (
+                ChannelSender::Unbounded(sender),
+                ChannelReceiver::Unbounded(receiver),
+            )
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ChannelSender {
+    Bounded(Sender<i64>),
+    Unbounded(UnboundedSender<i64>),
+}
+
+impl ChannelSender {
+    pub async fn send(&self, value: i64) -> Result<(), SendError<i64>> {
+        match self {
+            ChannelSender::Bounded(sender) => sender.send(value).await,
+            ChannelSender::Unbounded(sender) => sender.send(value),
+        }
+    }
+}
+
+impl From<Sender<i64>> for ChannelSender {
+    fn from(sender: Sender<i64>) -> Self {
+        ChannelSender::Bounded(sender)
+    }
+}
+
+impl From<UnboundedSender<i64>> for ChannelSender {
+    fn from(sender: UnboundedSender<i64>) -> Self {
+        ChannelSender::Unbounded(sender)
+    }
+}
+
+#[derive(Debug)]
+pub enum ChannelReceiver {
+    Bounded(Receiver<i64>),
+    Unbounded(UnboundedReceiver<i64>),
+}
+
+impl ChannelReceiver {
+    pub async fn recv(&mut self) -> Option<i64> {
+        match self {
+            ChannelReceiver::Bounded(receiver) => receiver.recv().await,
+            ChannelReceiver::Unbounded(receiver) => receiver.recv().await,
+        }
+    }
+
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<i64>> {
+        match self {
+            ChannelReceiver::Bounded(receiver) => receiver.poll_recv(cx),
+            ChannelReceiver::Unbounded(receiver) => receiver.poll_recv(cx),
+        }
+    }
+
+    pub fn try_recv(&mut self) -> Result<i64, TryRecvError> {
+        match self {
+            ChannelReceiver::Bounded(receiver) => receiver.try_recv(),
+            ChannelReceiver::Unbounded(receiver) => receiver.try_recv(),
+        }
+    }
+}
+
+impl From<Receiver<i64>> for ChannelReceiver {
+    fn from(receiver: Receiver<i64>) -> Self {
+        ChannelReceiver::Bounded(receiver)
+    }
+}
+
+impl From<UnboundedReceiver<i64>> for ChannelReceiver {
+    fn from(receiver: UnboundedReceiver<i64>) -> Self {
+        ChannelReceiver::Unbounded(receiver)
+    }
+}
+
+impl AsyncIntcodeInput for ChannelReceiver {
+    fn read_async(&mut self) -> IoFuture<'_, Option<i64>> {
+        Box::pin(self.recv())
+    }
+
+    fn try_read(&mut self) -> Option<i64> {
+        self.try_recv().ok()
+    }
+}
+
+impl AsyncIntcodeOutput for ChannelSender {
+    fn write_async(&mut self, value: i64) -> IoFuture<'_, bool> {
+        Box::pin(async move { self.send(value).await.is_ok() })
+    }
+}
@@ -0,0 +1,122 @@
+#[cfg(feature = "async")]
+use std::time::Duration;
+
+use crate::{
+    intcode_computer::{Dialect, IntcodeComputer, StatusMonitor},
+    intcode_io::{AsyncIntcodeInput, AsyncIntcodeOutput},
+    replay::Recorder,
+};
+
+// Collects everything needed to set up a computer so the same configuration can build any number
+// of them. Observers are shared by every computer built from the same builder.
+#[derive(Debug, Clone)]
+pub struct ComputerBuilder {
+    memory: Vec<i64>,
+    patches: Vec<(usize, i64)>,
+    default_input: Option<i64>,
+    #[cfg(feature = "async")]
+    input_timeout: Option<Duration>,
+    instruction_limit: Option<u64>,
+    dialect: Dialect,
+    recorder: Option<Recorder>,
+    monitor: Option<StatusMonitor>,
+}
+
+impl ComputerBuilder {
+    pub fn new(memory: Vec<i64>) -> Self {
+        ComputerBuilder {
+            memory,
+            patches: Vec::new(),
+            default_input: None,
+            #[cfg(feature = "async")]
+            input_timeout: None,
+            instruction_limit: None,
+            dialect: Dialect::default(),
+            recorder: None,
+            monitor: None,
+        }
+    }
+
+    pub fn parse(program: &str) -> Self {
+        ComputerBuilder::new(IntcodeComputer::parse_memory(program))
+    }
+
+    // Patches are applied in order after the program is loaded, so later ones win
+    pub fn patch(mut self, address: usize, value: i64) -> Self {
+        self.patches.push((address, value));
+        self
+    }
+
+    pub fn non_blocking_input(mut self, default_input: i64) -> Self {
+        self.default_input = Some(default_input);
+        self
+    }
+
+    #[cfg(feature = "async")]
+    pub fn input_timeout(mut self, timeout: Duration) -> Self {
+        self.input_timeout = Some(timeout);
+        self
+    }
+
+    pub fn instruction_limit(mut self, limit: u64) -> Self {
+        self.instruction_limit = Some(limit);
+        self
+    }
+
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    pub fn recorder(mut self, recorder: Recorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    pub fn status_monitor(mut self, monitor: StatusMonitor) -> Self {
+        self.monitor = Some(monitor);
+        self
+    }
+
+    pub fn build(&self) -> IntcodeComputer {
+        self.configure(IntcodeComputer::from_memory(
+            self.memory.clone(),
+            None,
+            None,
+        ))
+    }
+
+    pub fn build_with_io(
+        &self,
+        computer_input: impl AsyncIntcodeInput + Send + 'static,
+        computer_output: impl AsyncIntcodeOutput + Send + 'static,
+    ) -> IntcodeComputer {
+        self.configure(IntcodeComputer::from_memory(
+            self.memory.clone(),
+            Some(Box::new(computer_input)),
+            Some(Box::new(computer_output)),
+        ))
+    }
+
+    fn configure(&self, mut computer: IntcodeComputer) -> IntcodeComputer {
+        for (address, value) in &self.patches {
+            computer.set_memory(*address, *value);
+        }
+        if let Some(default_input) = self.default_input {
+            computer.set_non_blocking_input(default_input);
+        }
+        #[cfg(feature = "async")]
+        if let Some(timeout) = self.input_timeout {
+            computer.set_input_timeout(timeout);
+        }
+        computer.set_instruction_limit(self.instruction_limit);
+        computer.set_dialect(self.dialect);
+        if let Some(recorder) = &self.recorder {
+            computer.attach_recorder(recorder.clone());
+        }
+        if let Some(monitor) = &self.monitor {
+            computer.attach_status_monitor(monitor.clone());
+        }
+        computer
+    }
+}
@@ -0,0 +1,127 @@
+use tokio::sync::watch;
+
+use crate::intcode_computer::{ComputerStatus, ExecutionState};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlCommand {
+    Run,
+    Pause,
+    Cancel,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComputerState {
+    pub memory: Vec<i64>,
+    pub status: ComputerStatus,
+}
+
+// Stopped is set once the computer has returned from run, after which no further commands will be
+// acted on until it is run again
+#[derive(Debug, Clone, Default)]
+struct Report {
+    state: Option<ComputerState>,
+    stopped: bool,
+}
+
+impl Report {
+    fn is_in(&self, target: ExecutionState) -> bool {
+        self.state
+            .as_ref()
+            .is_some_and(|state| state.status.state == target)
+    }
+}
+
+pub struct ComputerControl {
+    command: watch::Sender<ControlCommand>,
+    state: watch::Receiver<Report>,
+}
+
+impl ComputerControl {
+    pub(crate) fn new() -> (Self, ControlListener) {
+        let (command_sender, command_receiver) = watch::channel(ControlCommand::Run);
+        let (state_sender, state_receiver) = watch::channel(Report::default());
+        (
+            ComputerControl {
+                command: command_sender,
+                state: state_receiver,
+            },
+            ControlListener {
+                command: command_receiver,
+                state: state_sender,
+            },
+        )
+    }
+
+    // Returns the state at the instruction boundary where the computer stopped, or None if the
+    // computer was dropped before it could report one. A computer that is already paused or has
+    // stopped running returns its last state straight away.
+    pub async fn pause(&mut self) -> Option<ComputerState> {
+        self.send_and_wait(ControlCommand::Pause, ExecutionState::Paused)
+            .await
+    }
+
+    pub fn resume(&self) {
+        let _ = self.command.send(ControlCommand::Run);
+    }
+
+    pub async fn cancel(&mut self) -> Option<ComputerState> {
+        self.send_and_wait(ControlCommand::Cancel, ExecutionState::Cancelled)
+            .await
+    }
+
+    pub fn last_state(&self) -> Option<ComputerState> {
+        self.state.borrow().state.clone()
+    }
+
+    async fn send_and_wait(
+        &mut self,
+        command: ControlCommand,
+        target: ExecutionState,
+    ) -> Option<ComputerState> {
+        let report = self.state.borrow_and_update().clone();
+        if report.stopped || (*self.command.borrow() == command && report.is_in(target)) {
+            return report.state;
+        }
+        if self.command.send(command).is_err() {
+            return self.last_state();
+        }
+        loop {
+            if self.state.changed().await.is_err() {
+                return self.last_state();
+            }
+            let report = self.state.borrow_and_update().clone();
+            if report.stopped || report.is_in(target) {
+                return report.state;
+            }
+        }
+    }
+}
+
+pub(crate) struct ControlListener {
+    command: watch::Receiver<ControlCommand>,
+    state: watch::Sender<Report>,
+}
+
+impl ControlListener {
+    pub(crate) fn current_command(&mut self) -> ControlCommand {
+        *self.command.borrow_and_update()
+    }
+
+    // Returns false once the handle has been dropped and no further commands can arrive
+    pub(crate) async fn command_changed(&mut self) -> bool {
+        self.command.changed().await.is_ok()
+    }
+
+    pub(crate) async fn interrupted(&mut self) {
+        if !self.command_changed().await {
+            std::future::pending::<()>().await;
+        }
+    }
+
+    pub(crate) fn report_state(&self, state: ComputerState, stopped: bool) {
+        let _ = self.state.send(Report {
+            state: Some(state),
+            stopped,
+        });
+    }
+}
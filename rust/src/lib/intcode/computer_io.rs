@@ -1,40 +1,165 @@
-use tokio::sync::mpsc::{Receiver, Sender};
+use std::{
+    collections::VecDeque,
+    future::{poll_fn, Future},
+    task::{Context, Poll},
+    time::Instant,
+};
 
-use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::io::{
+    AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader, Lines, Stdin, Stdout,
+};
 
-pub struct StdInComputerInput {
-    computer_input: Sender<i64>,
+use crate::{
+    channel::{channel, ChannelCapacity, ChannelReceiver, ChannelSender},
+    intcode_io::{output_text, AsyncIntcodeInput, AsyncIntcodeOutput, IoFuture},
+    transcript::{Transcript, TranscriptEntry},
+};
+
+// Prompts for a line each time the computer asks for input, queueing any extra values on it
+pub struct PromptedComputerInput<R, W> {
+    lines: Lines<R>,
+    prompt_output: W,
+    prompt: String,
+    ascii: bool,
+    queued: VecDeque<i64>,
 }
 
+pub type StdInComputerInput = PromptedComputerInput<BufReader<Stdin>, Stdout>;
+
 impl StdInComputerInput {
-    pub fn new() -> (Self, Receiver<i64>) {
-        let (sender, receiver) = tokio::sync::mpsc::channel(1);
-        (
-            StdInComputerInput {
-                computer_input: sender,
-            },
-            receiver,
-        )
+    pub fn new() -> Self {
+        StdInComputerInput::default()
     }
+}
 
-    pub async fn run(&self) {
-        let stdin = tokio::io::stdin();
-        let reader = BufReader::new(stdin);
-        let mut lines = reader.lines();
-        while let Some(line) = lines.next_line().await.unwrap() {
-            let input = line.parse::<i64>().unwrap();
-            self.computer_input.send(input).await.unwrap();
+impl Default for StdInComputerInput {
+    fn default() -> Self {
+        PromptedComputerInput::from_reader(BufReader::new(tokio::io::stdin()), tokio::io::stdout())
+    }
+}
+
+impl<R, W> PromptedComputerInput<R, W>
+where
+    R: AsyncBufRead + Unpin + Send,
+    W: AsyncWrite + Unpin + Send,
+{
+    pub fn from_reader(reader: R, prompt_output: W) -> Self {
+        PromptedComputerInput {
+            lines: reader.lines(),
+            prompt_output,
+            prompt: "INPUT: ".to_string(),
+            ascii: false,
+            queued: VecDeque::new(),
         }
     }
+
+    pub fn with_prompt(mut self, prompt: &str) -> Self {
+        self.prompt = prompt.to_string();
+        self
+    }
+
+    pub fn ascii(mut self) -> Self {
+        self.ascii = true;
+        self
+    }
+
+    async fn read_line(&mut self) -> Option<Vec<i64>> {
+        loop {
+            self.write_prompt(&self.prompt.clone()).await;
+            let line = self.lines.next_line().await.ok()??;
+            if self.ascii {
+                return Some(line.bytes().map(i64::from).chain([b'\n' as i64]).collect());
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+            match line
+                .split(',')
+                .map(|value| value.trim().parse::<i64>())
+                .collect::<Result<Vec<_>, _>>()
+            {
+                Ok(values) => return Some(values),
+                Err(_) => {
+                    self.write_prompt(&format!("Invalid input: {}\n", line.trim()))
+                        .await
+                }
+            }
+        }
+    }
+
+    async fn write_prompt(&mut self, text: &str) {
+        let _ = self.prompt_output.write_all(text.as_bytes()).await;
+        let _ = self.prompt_output.flush().await;
+    }
+}
+
+impl<R, W> AsyncIntcodeInput for PromptedComputerInput<R, W>
+where
+    R: AsyncBufRead + Unpin + Send,
+    W: AsyncWrite + Unpin + Send,
+{
+    fn read_async(&mut self) -> IoFuture<'_, Option<i64>> {
+        Box::pin(async move {
+            if self.queued.is_empty() {
+                let values = self.read_line().await?;
+                self.queued.extend(values);
+            }
+            self.queued.pop_front()
+        })
+    }
+
+    // Never prompts
+    fn try_read(&mut self) -> Option<i64> {
+        self.queued.pop_front()
+    }
+
+    // Reading a line may wait for the user, so non-blocking mode would never prompt
+    fn supports_try_read(&self) -> bool {
+        false
+    }
+}
+
+// Flushes after every value
+pub struct WriterComputerOutput<W> {
+    writer: W,
+    ascii: bool,
+}
+
+impl<W: AsyncWrite + Unpin + Send> WriterComputerOutput<W> {
+    pub fn new(writer: W) -> Self {
+        WriterComputerOutput {
+            writer,
+            ascii: false,
+        }
+    }
+
+    pub fn ascii(mut self) -> Self {
+        self.ascii = true;
+        self
+    }
+}
+
+impl<W: AsyncWrite + Unpin + Send> AsyncIntcodeOutput for WriterComputerOutput<W> {
+    fn write_async(&mut self, value: i64) -> IoFuture<'_, bool> {
+        Box::pin(async move {
+            let text = output_text(value, self.ascii);
+            self.writer.write_all(text.as_bytes()).await.is_ok()
+                && self.writer.flush().await.is_ok()
+        })
+    }
 }
 
 pub struct StdOutComputerOutput {
-    computer_output: Receiver<i64>,
+    computer_output: ChannelReceiver,
 }
 
 impl StdOutComputerOutput {
-    pub fn new() -> (Self, Sender<i64>) {
-        let (sender, receiver) = tokio::sync::mpsc::channel(1);
+    pub fn new() -> (Self, ChannelSender) {
+        StdOutComputerOutput::with_capacity(ChannelCapacity::default())
+    }
+
+    pub fn with_capacity(capacity: ChannelCapacity) -> (Self, ChannelSender) {
+        let (sender, receiver) = channel(capacity);
         (
             StdOutComputerOutput {
                 computer_output: receiver,
@@ -51,13 +176,17 @@ impl StdOutComputerOutput {
 }
 
 pub struct StringComputerInput {
-    computer_input: Sender<i64>,
+    computer_input: ChannelSender,
     input: String,
 }
 
 impl StringComputerInput {
-    pub fn new(input: String) -> (Self, Receiver<i64>) {
-        let (sender, receiver) = tokio::sync::mpsc::channel(1);
+    pub fn new(input: String) -> (Self, ChannelReceiver) {
+        StringComputerInput::with_capacity(input, ChannelCapacity::default())
+    }
+
+    pub fn with_capacity(input: String, capacity: ChannelCapacity) -> (Self, ChannelReceiver) {
+        let (sender, receiver) = channel(capacity);
         (
             StringComputerInput {
                 computer_input: sender,
@@ -76,17 +205,34 @@ impl StringComputerInput {
 }
 
 pub struct StringComputerOutput {
-    computer_output: Receiver<i64>,
+    computer_output: ChannelReceiver,
     output: String,
+    separator: String,
 }
 
 impl StringComputerOutput {
-    pub fn new() -> (Self, Sender<i64>) {
-        let (sender, receiver) = tokio::sync::mpsc::channel(1);
+    pub fn new() -> (Self, ChannelSender) {
+        StringComputerOutput::with_separator("\n")
+    }
+
+    pub fn with_separator(separator: &str) -> (Self, ChannelSender) {
+        StringComputerOutput::with_separator_and_capacity(separator, ChannelCapacity::default())
+    }
+
+    pub fn with_capacity(capacity: ChannelCapacity) -> (Self, ChannelSender) {
+        StringComputerOutput::with_separator_and_capacity("\n", capacity)
+    }
+
+    pub fn with_separator_and_capacity(
+        separator: &str,
+        capacity: ChannelCapacity,
+    ) -> (Self, ChannelSender) {
+        let (sender, receiver) = channel(capacity);
         (
             StringComputerOutput {
                 computer_output: receiver,
                 output: String::new(),
+                separator: separator.to_string(),
             },
             sender,
         )
@@ -94,33 +240,322 @@ impl StringComputerOutput {
 
     pub async fn run(&mut self) -> String {
         while let Some(output) = self.computer_output.recv().await {
+            if !self.output.is_empty() {
+                self.output.push_str(&self.separator);
+            }
             self.output.push_str(&output.to_string());
         }
         self.output.clone()
     }
 }
 
+pub struct VecComputerOutput {
+    computer_output: ChannelReceiver,
+    output: Vec<i64>,
+}
+
+impl VecComputerOutput {
+    pub fn new() -> (Self, ChannelSender) {
+        VecComputerOutput::with_capacity(ChannelCapacity::default())
+    }
+
+    pub fn with_capacity(capacity: ChannelCapacity) -> (Self, ChannelSender) {
+        let (sender, receiver) = channel(capacity);
+        (
+            VecComputerOutput {
+                computer_output: receiver,
+                output: Vec::new(),
+            },
+            sender,
+        )
+    }
+
+    pub async fn run(&mut self) -> Vec<i64> {
+        while let Some(output) = self.computer_output.recv().await {
+            self.output.push(output);
+        }
+        self.output.clone()
+    }
+}
+
+pub struct AsciiComputerInput {
+    computer_input: ChannelSender,
+    input: String,
+}
+
+impl AsciiComputerInput {
+    pub fn new(input: String) -> (Self, ChannelReceiver) {
+        AsciiComputerInput::with_capacity(input, ChannelCapacity::default())
+    }
+
+    pub fn with_capacity(input: String, capacity: ChannelCapacity) -> (Self, ChannelReceiver) {
+        let (sender, receiver) = channel(capacity);
+        (
+            AsciiComputerInput {
+                computer_input: sender,
+                input,
+            },
+            receiver,
+        )
+    }
+
+    pub async fn run(&self) {
+        for line in self.input.lines() {
+            for byte in line.bytes() {
+                self.computer_input.send(byte as i64).await.unwrap();
+            }
+            self.computer_input.send('\n' as i64).await.unwrap();
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AsciiOutput {
+    pub text: String,
+    pub values: Vec<i64>,
+}
+
+// Values outside of the ASCII range are collected separately
+pub struct AsciiComputerOutput {
+    computer_output: ChannelReceiver,
+    output: AsciiOutput,
+}
+
+impl AsciiComputerOutput {
+    pub fn new() -> (Self, ChannelSender) {
+        AsciiComputerOutput::with_capacity(ChannelCapacity::default())
+    }
+
+    pub fn with_capacity(capacity: ChannelCapacity) -> (Self, ChannelSender) {
+        let (sender, receiver) = channel(capacity);
+        (
+            AsciiComputerOutput {
+                computer_output: receiver,
+                output: AsciiOutput::default(),
+            },
+            sender,
+        )
+    }
+
+    pub async fn run(&mut self) -> AsciiOutput {
+        while let Some(output) = self.computer_output.recv().await {
+            match u8::try_from(output) {
+                Ok(byte) if byte.is_ascii() => self.output.text.push(byte as char),
+                _ => self.output.values.push(output),
+            }
+        }
+        self.output.clone()
+    }
+}
+
 pub struct LoggingConnector {
-    output: Sender<i64>,
-    input: Receiver<i64>,
-    log: String,
+    output: ChannelSender,
+    input: ChannelReceiver,
+    source: String,
+    destination: String,
+    start: Instant,
+    transcript: Transcript,
 }
 
 impl LoggingConnector {
-    pub fn new(output: Sender<i64>, input: Receiver<i64>) -> Self {
+    pub fn new(output: impl Into<ChannelSender>, input: impl Into<ChannelReceiver>) -> Self {
         LoggingConnector {
-            output,
-            input,
-            log: String::new(),
+            output: output.into(),
+            input: input.into(),
+            source: String::new(),
+            destination: String::new(),
+            start: Instant::now(),
+            transcript: Transcript::new(),
         }
     }
 
-    pub async fn run(&mut self) -> String {
+    pub fn with_names(mut self, source: &str, destination: &str) -> Self {
+        self.source = source.to_string();
+        self.destination = destination.to_string();
+        self
+    }
+
+    // Connectors sharing a start instant produce timestamps that can be merged into one transcript
+    pub fn with_start(mut self, start: Instant) -> Self {
+        self.start = start;
+        self
+    }
+
+    pub async fn run(&mut self) -> Transcript {
+        let mut sequence = 0;
         while let Some(data) = self.input.recv().await {
-            self.log.push_str(&data.to_string());
-            self.log.push('\n');
+            self.transcript.push(TranscriptEntry {
+                sequence,
+                timestamp: self.start.elapsed(),
+                source: self.source.clone(),
+                destination: self.destination.clone(),
+                value: data,
+            });
+            sequence += 1;
             let _ = self.output.send(data).await;
         }
-        self.log.clone()
+        self.transcript.clone()
+    }
+}
+
+pub struct TeeConnector {
+    outputs: Vec<ChannelSender>,
+    input: ChannelReceiver,
+}
+
+impl TeeConnector {
+    pub fn new(
+        outputs: impl IntoIterator<Item = impl Into<ChannelSender>>,
+        input: impl Into<ChannelReceiver>,
+    ) -> Self {
+        TeeConnector {
+            outputs: outputs.into_iter().map(Into::into).collect(),
+            input: input.into(),
+        }
+    }
+
+    // Every value is sent to all consumers at once, so a full consumer only holds back the next
+    // value. Consumers that have gone away are skipped.
+    pub async fn run(&mut self) {
+        while let Some(data) = self.input.recv().await {
+            let mut sends = self
+                .outputs
+                .iter()
+                .map(|output| Some(Box::pin(output.send(data))))
+                .collect::<Vec<_>>();
+            poll_fn(|cx| {
+                for send in &mut sends {
+                    if send
+                        .as_mut()
+                        .is_some_and(|send| send.as_mut().poll(cx).is_ready())
+                    {
+                        *send = None;
+                    }
+                }
+                if sends.iter().all(Option::is_none) {
+                    Poll::Ready(())
+                } else {
+                    Poll::Pending
+                }
+            })
+            .await;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MergeOrder {
+    // Takes one value from each producer in turn, waiting for the next one even if another
+    // producer already has a value, so the order does not depend on timing
+    #[default]
+    RoundRobin,
+    // Forwards whichever value arrives first, so a silent producer does not hold up the others
+    Arrival,
+}
+
+pub struct MergeConnector {
+    output: ChannelSender,
+    inputs: Vec<ChannelReceiver>,
+    order: MergeOrder,
+}
+
+impl MergeConnector {
+    pub fn new(
+        output: impl Into<ChannelSender>,
+        inputs: impl IntoIterator<Item = impl Into<ChannelReceiver>>,
+    ) -> Self {
+        MergeConnector {
+            output: output.into(),
+            inputs: inputs.into_iter().map(Into::into).collect(),
+            order: MergeOrder::default(),
+        }
+    }
+
+    pub fn with_order(mut self, order: MergeOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    // Producers drop out once they close. In arrival order the producers are checked in turn
+    // starting after the last one served, so a busy producer cannot starve the others.
+    pub async fn run(&mut self) {
+        let mut next = 0;
+        while !self.inputs.is_empty() {
+            let (index, data) = match self.order {
+                MergeOrder::RoundRobin => {
+                    let index = next % self.inputs.len();
+                    (index, self.inputs[index].recv().await)
+                }
+                MergeOrder::Arrival => poll_fn(|cx| self.poll_inputs(next, cx)).await,
+            };
+            match data {
+                Some(data) => {
+                    let _ = self.output.send(data).await;
+                    next = index + 1;
+                }
+                None => {
+                    self.inputs.remove(index);
+                    next = index;
+                }
+            }
+        }
+    }
+
+    fn poll_inputs(&mut self, start: usize, cx: &mut Context<'_>) -> Poll<(usize, Option<i64>)> {
+        let count = self.inputs.len();
+        for offset in 0..count {
+            let index = (start + offset) % count;
+            if let Poll::Ready(data) = self.inputs[index].poll_recv(cx) {
+                return Poll::Ready((index, data));
+            }
+        }
+        Poll::Pending
+    }
+}
+
+type Transform = Box<dyn FnMut(i64) -> Option<i64> + Send>;
+
+pub struct TransformConnector {
+    output: ChannelSender,
+    input: ChannelReceiver,
+    transform: Transform,
+}
+
+impl TransformConnector {
+    // Values for which the transform returns None are dropped
+    pub fn new(
+        output: impl Into<ChannelSender>,
+        input: impl Into<ChannelReceiver>,
+        transform: impl FnMut(i64) -> Option<i64> + Send + 'static,
+    ) -> Self {
+        TransformConnector {
+            output: output.into(),
+            input: input.into(),
+            transform: Box::new(transform),
+        }
+    }
+
+    pub fn map(
+        output: impl Into<ChannelSender>,
+        input: impl Into<ChannelReceiver>,
+        mut map: impl FnMut(i64) -> i64 + Send + 'static,
+    ) -> Self {
+        TransformConnector::new(output, input, move |data| Some(map(data)))
+    }
+
+    pub fn filter(
+        output: impl Into<ChannelSender>,
+        input: impl Into<ChannelReceiver>,
+        mut filter: impl FnMut(i64) -> bool + Send + 'static,
+    ) -> Self {
+        TransformConnector::new(output, input, move |data| filter(data).then_some(data))
+    }
+
+    pub async fn run(&mut self) {
+        while let Some(data) = self.input.recv().await {
+            if let Some(data) = (self.transform)(data) {
+                let _ = self.output.send(data).await;
+            }
+        }
     }
 }
@@ -0,0 +1,67 @@
+use std::fmt;
+
+use crate::intcode_computer::{Instruction, Parameter, ParamtereMode};
+
+// Decodes the instruction starting at the given address, or returns None if the values there do
+// not form a valid instruction
+pub fn disassemble_instruction(memory: &[i64], address: usize) -> Option<String> {
+    Instruction::try_from_memory_address(memory, address).map(|instruction| instruction.to_string())
+}
+
+// Walks the memory linearly, emitting raw data for anything that cannot be decoded
+pub fn disassemble(memory: &[i64]) -> Vec<(usize, String)> {
+    decode(memory)
+        .into_iter()
+        .map(|(address, instruction)| match instruction {
+            Some(instruction) => (address, instruction.to_string()),
+            None => (address, format!("data {}", memory[address])),
+        })
+        .collect()
+}
+
+// Walks the memory linearly, with None for single values that do not form a valid instruction
+pub(crate) fn decode(memory: &[i64]) -> Vec<(usize, Option<Instruction>)> {
+    let mut result = Vec::new();
+    let mut address = 0;
+    while address < memory.len() {
+        let instruction = Instruction::try_from_memory_address(memory, address);
+        let length = instruction.as_ref().map_or(1, Instruction::length);
+        result.push((address, instruction));
+        address += length;
+    }
+    result
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::Add { lhs, rhs, dst } => write!(f, "add {}, {}, {}", lhs, rhs, dst),
+            Instruction::Multiply { lhs, rhs, dst } => write!(f, "mul {}, {}, {}", lhs, rhs, dst),
+            Instruction::Halt => write!(f, "hlt"),
+            Instruction::Input { dst } => write!(f, "in {}", dst),
+            Instruction::Output { src } => write!(f, "out {}", src),
+            Instruction::JumpIfTrue {
+                condition,
+                jump_address,
+            } => write!(f, "jnz {}, {}", condition, jump_address),
+            Instruction::JumpIfFalse {
+                condition,
+                jump_address,
+            } => write!(f, "jz {}, {}", condition, jump_address),
+            Instruction::LessThan { lhs, rhs, dst } => write!(f, "lt {}, {}, {}", lhs, rhs, dst),
+            Instruction::Equals { lhs, rhs, dst } => write!(f, "eq {}, {}, {}", lhs, rhs, dst),
+            Instruction::RelativeBaseOffset { offset } => write!(f, "arb {}", offset),
+        }
+    }
+}
+
+impl fmt::Display for Parameter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.mode {
+            ParamtereMode::Position => write!(f, "[{}]", self.value),
+            ParamtereMode::Immediate => write!(f, "{}", self.value),
+            ParamtereMode::Relative if self.value < 0 => write!(f, "[rb{}]", self.value),
+            ParamtereMode::Relative => write!(f, "[rb+{}]", self.value),
+        }
+    }
+}
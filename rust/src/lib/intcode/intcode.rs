@@ -1,2 +1,21 @@
+pub mod amplifiers;
+#[cfg(feature = "async")]
+pub mod channel;
+pub mod computer_builder;
+#[cfg(feature = "async")]
+pub mod computer_control;
+#[cfg(feature = "async")]
 pub mod computer_io;
+pub mod disassembler;
 pub mod intcode_computer;
+pub mod intcode_io;
+pub mod memory_device;
+pub mod memory_diff;
+#[cfg(feature = "async")]
+pub mod network;
+pub mod replay;
+pub mod scheduler;
+#[cfg(feature = "async")]
+pub mod server;
+pub mod topology;
+pub mod transcript;
@@ -1,13 +1,248 @@
-use tokio::sync::mpsc::{Receiver, Sender};
+use std::{
+    collections::VecDeque,
+    fmt,
+    future::Future,
+    ops::Range,
+    pin::pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Wake, Waker},
+    thread::{self, Thread},
+    time::Duration,
+};
+
+#[cfg(feature = "async")]
+use crate::{
+    channel::ChannelSender,
+    computer_control::{ComputerControl, ComputerState, ControlCommand, ControlListener},
+    computer_io::{StringComputerInput, StringComputerOutput, VecComputerOutput},
+};
+use crate::{
+    intcode_io::{AsyncIntcodeInput, AsyncIntcodeOutput},
+    memory_device::{DeviceMap, MemoryDevice},
+    replay::{IoEvent, Recorder},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutionState {
+    Running,
+    WaitingForInput,
+    Paused,
+    Cancelled,
+    Halted,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepEvent {
+    Output(i64),
+    WaitingForInput,
+    Halted,
+    Failed(IntcodeError),
+}
+
+// The instruction sets of the days that introduced them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Dialect {
+    Day02,
+    Day05,
+    #[default]
+    Day09,
+}
 
-use crate::computer_io::{StringComputerInput, StringComputerOutput};
+impl Dialect {
+    fn supports(self, value: i64) -> bool {
+        let opcode = value % 100;
+        let (opcodes, max_mode): (&[i64], i64) = match self {
+            Dialect::Day02 => (&[1, 2, 99], 0),
+            Dialect::Day05 => (&[1, 2, 3, 4, 5, 6, 7, 8, 99], 1),
+            Dialect::Day09 => return true,
+        };
+        let Some(parameter_count) = Instruction::parameter_count(opcode) else {
+            return false;
+        };
+        opcodes.contains(&opcode)
+            && (0..parameter_count).all(|parameter_index| {
+                (value / 10_i64.pow(parameter_index as u32 + 2)) % 10 <= max_mode
+            })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HaltReason {
+    Halted,
+    InputExhausted,
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunResult {
+    pub outputs: Vec<i64>,
+    pub memory: Vec<i64>,
+    pub instruction_count: u64,
+    pub halt_reason: HaltReason,
+    pub error: Option<IntcodeError>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComputerStatus {
+    pub instruction_pointer: usize,
+    pub relative_base: i64,
+    pub state: ExecutionState,
+    pub instruction_count: u64,
+    pub memory_size: usize,
+    pub idle_count: u64,
+}
+
+// Counts the consecutive non-blocking reads that found no queued input
+#[derive(Debug, Clone, Default)]
+pub struct IdleCounter(Arc<AtomicU64>);
+
+impl IdleCounter {
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    fn increment(&self) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn reset(&self) {
+        self.0.store(0, Ordering::SeqCst);
+    }
+}
+
+// Follows the status of a computer from another task, updated whenever its execution state changes
+#[derive(Debug, Clone)]
+pub struct StatusMonitor(Arc<Mutex<ComputerStatus>>);
+
+impl Default for StatusMonitor {
+    fn default() -> Self {
+        StatusMonitor::new()
+    }
+}
+
+impl StatusMonitor {
+    pub fn new() -> Self {
+        StatusMonitor(Arc::new(Mutex::new(ComputerStatus {
+            instruction_pointer: 0,
+            relative_base: 0,
+            state: ExecutionState::Running,
+            instruction_count: 0,
+            memory_size: 0,
+            idle_count: 0,
+        })))
+    }
+
+    pub fn get(&self) -> ComputerStatus {
+        self.0.lock().unwrap().clone()
+    }
+
+    fn publish(&self, status: ComputerStatus) {
+        *self.0.lock().unwrap() = status;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntcodeError {
+    InputTimeout {
+        instruction_pointer: usize,
+        timeout: Duration,
+    },
+    // run_blocking has no timer to enforce an input timeout with
+    TimeoutWithoutRuntime {
+        timeout: Duration,
+    },
+    InputClosed {
+        instruction_pointer: usize,
+    },
+    InputFailed {
+        instruction_pointer: usize,
+    },
+    OutputClosed {
+        instruction_pointer: usize,
+    },
+    InstructionLimitExceeded {
+        instruction_pointer: usize,
+        limit: u64,
+    },
+    UnsupportedInstruction {
+        instruction_pointer: usize,
+        value: i64,
+        dialect: Dialect,
+    },
+}
+
+impl fmt::Display for IntcodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntcodeError::InputTimeout {
+                instruction_pointer,
+                timeout,
+            } => write!(
+                f,
+                "No input received within {:?} at instruction {}",
+                timeout, instruction_pointer
+            ),
+            IntcodeError::TimeoutWithoutRuntime { timeout } => write!(
+                f,
+                "An input timeout of {:?} needs an async runtime",
+                timeout
+            ),
+            IntcodeError::InputClosed {
+                instruction_pointer,
+            } => write!(f, "Input closed at instruction {}", instruction_pointer),
+            IntcodeError::InputFailed {
+                instruction_pointer,
+            } => write!(f, "Input failed at instruction {}", instruction_pointer),
+            IntcodeError::OutputClosed {
+                instruction_pointer,
+            } => write!(f, "Output closed at instruction {}", instruction_pointer),
+            IntcodeError::InstructionLimitExceeded {
+                instruction_pointer,
+                limit,
+            } => write!(
+                f,
+                "Instruction limit of {} reached at instruction {}",
+                limit, instruction_pointer
+            ),
+            IntcodeError::UnsupportedInstruction {
+                instruction_pointer,
+                value,
+                dialect,
+            } => write!(
+                f,
+                "Instruction {} at {} is not supported by {:?}",
+                value, instruction_pointer, dialect
+            ),
+        }
+    }
+}
+
+impl std::error::Error for IntcodeError {}
 
 pub struct IntcodeComputer {
     memory: Vec<i64>,
     instruction_pointer: usize,
     relative_base: i64,
-    input: Option<Receiver<i64>>,
-    output: Option<Sender<i64>>,
+    input: Option<Box<dyn AsyncIntcodeInput + Send>>,
+    output: Option<Box<dyn AsyncIntcodeOutput + Send>>,
+    output_closed: bool,
+    pending_output: Option<(usize, i64)>,
+    #[cfg(feature = "async")]
+    control: Option<ControlListener>,
+    state: ExecutionState,
+    instruction_count: u64,
+    devices: DeviceMap,
+    default_input: Option<i64>,
+    #[cfg(feature = "async")]
+    input_timeout: Option<Duration>,
+    instruction_limit: Option<u64>,
+    dialect: Dialect,
+    idle_counter: IdleCounter,
+    recorder: Option<Recorder>,
+    monitor: Option<StatusMonitor>,
 }
 
 impl IntcodeComputer {
@@ -17,43 +252,364 @@ impl IntcodeComputer {
 
     pub fn new_with_io(
         input: &str,
-        computer_input: Receiver<i64>,
-        computer_output: Sender<i64>,
+        computer_input: impl AsyncIntcodeInput + Send + 'static,
+        computer_output: impl AsyncIntcodeOutput + Send + 'static,
     ) -> Self {
-        IntcodeComputer::new_internal(input, Some(computer_input), Some(computer_output))
+        IntcodeComputer::new_internal(
+            input,
+            Some(Box::new(computer_input)),
+            Some(Box::new(computer_output)),
+        )
     }
 
     fn new_internal(
         input: &str,
-        computer_input: Option<Receiver<i64>>,
-        computer_output: Option<Sender<i64>>,
+        computer_input: Option<Box<dyn AsyncIntcodeInput + Send>>,
+        computer_output: Option<Box<dyn AsyncIntcodeOutput + Send>>,
     ) -> Self {
         let memory = IntcodeComputer::parse_memory(input);
+        IntcodeComputer::from_memory(memory, computer_input, computer_output)
+    }
+
+    pub(crate) fn from_memory(
+        memory: Vec<i64>,
+        computer_input: Option<Box<dyn AsyncIntcodeInput + Send>>,
+        computer_output: Option<Box<dyn AsyncIntcodeOutput + Send>>,
+    ) -> Self {
         IntcodeComputer {
             memory,
             instruction_pointer: 0,
             relative_base: 0,
             input: computer_input,
             output: computer_output,
+            output_closed: false,
+            pending_output: None,
+            #[cfg(feature = "async")]
+            control: None,
+            state: ExecutionState::Running,
+            instruction_count: 0,
+            devices: DeviceMap::default(),
+            default_input: None,
+            #[cfg(feature = "async")]
+            input_timeout: None,
+            instruction_limit: None,
+            dialect: Dialect::default(),
+            idle_counter: IdleCounter::default(),
+            recorder: None,
+            monitor: None,
         }
     }
 
-    fn parse_memory(input: &str) -> Vec<i64> {
+    pub(crate) fn parse_memory(input: &str) -> Vec<i64> {
         input
             .split(',')
             .map(|num| num.parse::<i64>().unwrap())
             .collect::<Vec<_>>()
     }
 
-    pub async fn run(&mut self) {
-        loop {
+    #[cfg(feature = "async")]
+    pub fn control(&mut self) -> ComputerControl {
+        let (control, listener) = ComputerControl::new();
+        self.control = Some(listener);
+        control
+    }
+
+    // A timed out read leaves the instruction pointer on the input instruction, so the computer
+    // can be run again once input is available
+    pub async fn run(&mut self) -> Result<(), IntcodeError> {
+        #[cfg(feature = "async")]
+        self.report_state(false);
+        let result = self.run_instructions().await;
+        // Lets pending pause and cancel requests resolve once nothing is left to act on them
+        #[cfg(feature = "async")]
+        self.report_state(true);
+        result
+    }
+
+    async fn run_instructions(&mut self) -> Result<(), IntcodeError> {
+        while !self.is_halted() {
+            #[cfg(feature = "async")]
+            if !self.wait_for_control().await {
+                break;
+            }
+            self.check_instruction()?;
             let instruction =
                 Instruction::from_memory_address(&self.memory, self.instruction_pointer);
             match instruction {
-                Instruction::Halt => break,
-                _ => self.execute_instruction(&instruction).await,
+                Instruction::Halt => self.halt(),
+                _ => {
+                    if self.execute_instruction(&instruction).await? {
+                        self.instruction_count += 1;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Runs on the current thread without an async runtime, so it fails straight away if an input
+    // timeout is set
+    pub fn run_blocking(&mut self) -> Result<(), IntcodeError> {
+        #[cfg(feature = "async")]
+        if let Some(timeout) = self.input_timeout {
+            return Err(IntcodeError::TimeoutWithoutRuntime { timeout });
+        }
+        block_on(self.run())
+    }
+
+    // Feeds the inputs in order and collects every output until the program halts or asks for
+    // more input than it was given, which is reported as a closed input
+    pub fn run_with_inputs(&mut self, inputs: &[i64]) -> RunResult {
+        let mut input = inputs.iter().copied().collect::<VecDeque<_>>();
+        let mut outputs = Vec::new();
+        let (halt_reason, error) = loop {
+            match self.run_until_event(&mut input) {
+                StepEvent::Output(value) => outputs.push(value),
+                StepEvent::Halted => break (HaltReason::Halted, None),
+                StepEvent::WaitingForInput => {
+                    let error = IntcodeError::InputClosed {
+                        instruction_pointer: self.instruction_pointer,
+                    };
+                    break (HaltReason::InputExhausted, Some(error));
+                }
+                StepEvent::Failed(error) => break (HaltReason::Failed, Some(error)),
+            }
+        };
+        RunResult {
+            outputs,
+            memory: self.get_memory_snapshot(),
+            instruction_count: self.instruction_count,
+            halt_reason,
+            error,
+        }
+    }
+
+    // Runs until the next output, halt or read from an empty queue, ignoring the computer's own I/O
+    pub fn run_until_event(&mut self, input: &mut VecDeque<i64>) -> StepEvent {
+        while !self.is_halted() {
+            if let Err(error) = self.check_instruction() {
+                return StepEvent::Failed(error);
+            }
+            let instruction =
+                Instruction::from_memory_address(&self.memory, self.instruction_pointer);
+            match &instruction {
+                Instruction::Halt => self.halt(),
+                Instruction::Input { dst } => {
+                    let read_data = match (input.pop_front(), self.default_input) {
+                        (Some(read_data), _) => {
+                            self.idle_counter.reset();
+                            read_data
+                        }
+                        (None, Some(default_input)) => {
+                            self.idle_counter.increment();
+                            default_input
+                        }
+                        (None, None) => {
+                            self.set_state(ExecutionState::WaitingForInput);
+                            return StepEvent::WaitingForInput;
+                        }
+                    };
+                    self.set_state(ExecutionState::Running);
+                    self.finish_input(dst, read_data);
+                    self.instruction_count += 1;
+                }
+                Instruction::Output { src } => {
+                    let output_data = src.get_value(self);
+                    self.finish_output(output_data);
+                    self.instruction_count += 1;
+                    return StepEvent::Output(output_data);
+                }
+                _ => {
+                    self.execute_pure(&instruction);
+                    self.instruction_count += 1;
+                }
             }
         }
+        StepEvent::Halted
+    }
+
+    // Checked before every instruction, so the computer stops without executing it
+    fn check_instruction(&self) -> Result<(), IntcodeError> {
+        if let Some(limit) = self.instruction_limit {
+            if self.instruction_count >= limit {
+                return Err(IntcodeError::InstructionLimitExceeded {
+                    instruction_pointer: self.instruction_pointer,
+                    limit,
+                });
+            }
+        }
+        let value = self.get_memory(self.instruction_pointer);
+        if !self.dialect.supports(value) {
+            return Err(IntcodeError::UnsupportedInstruction {
+                instruction_pointer: self.instruction_pointer,
+                value,
+                dialect: self.dialect,
+            });
+        }
+        Ok(())
+    }
+
+    // Closing the output lets connected consumers see the end of the stream without the computer
+    // having to be dropped
+    fn halt(&mut self) {
+        self.instruction_count += 1;
+        self.set_state(ExecutionState::Halted);
+        self.output_closed |= self.output.take().is_some();
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.state == ExecutionState::Halted
+    }
+
+    pub fn instruction_pointer(&self) -> usize {
+        self.instruction_pointer
+    }
+
+    pub fn relative_base(&self) -> i64 {
+        self.relative_base
+    }
+
+    pub fn instruction_count(&self) -> u64 {
+        self.instruction_count
+    }
+
+    pub fn status(&self) -> ComputerStatus {
+        ComputerStatus {
+            instruction_pointer: self.instruction_pointer,
+            relative_base: self.relative_base,
+            state: self.state,
+            instruction_count: self.instruction_count,
+            memory_size: self.memory.len(),
+            idle_count: self.idle_counter.get(),
+        }
+    }
+
+    // Makes input instructions read the given value instead of waiting when no input is queued.
+    // Panics if the input cannot tell whether a value is queued.
+    pub fn set_non_blocking_input(&mut self, default_input: i64) {
+        if let Some(input) = &self.input {
+            assert!(
+                input.supports_try_read(),
+                "Input does not support non-blocking reads"
+            );
+        }
+        self.default_input = Some(default_input);
+    }
+
+    pub fn set_blocking_input(&mut self) {
+        self.default_input = None;
+    }
+
+    // Makes run fail when a blocking read waits longer than the timeout. Panics if the input
+    // blocks the thread while it waits, since the timeout could never interrupt it.
+    #[cfg(feature = "async")]
+    pub fn set_input_timeout(&mut self, timeout: Duration) {
+        if let Some(input) = &self.input {
+            assert!(input.supports_timeout(), "Input does not support timeouts");
+        }
+        self.input_timeout = Some(timeout);
+    }
+
+    #[cfg(feature = "async")]
+    pub fn clear_input_timeout(&mut self) {
+        self.input_timeout = None;
+    }
+
+    // Makes the computer fail instead of running forever once it has executed this many
+    // instructions
+    pub fn set_instruction_limit(&mut self, limit: Option<u64>) {
+        self.instruction_limit = limit;
+    }
+
+    pub fn set_dialect(&mut self, dialect: Dialect) {
+        self.dialect = dialect;
+    }
+
+    pub fn idle_counter(&self) -> IdleCounter {
+        self.idle_counter.clone()
+    }
+
+    pub fn status_monitor(&mut self) -> StatusMonitor {
+        let monitor = StatusMonitor::new();
+        self.attach_status_monitor(monitor.clone());
+        monitor
+    }
+
+    pub(crate) fn attach_status_monitor(&mut self, monitor: StatusMonitor) {
+        monitor.publish(self.status());
+        self.monitor = Some(monitor);
+    }
+
+    fn set_state(&mut self, state: ExecutionState) {
+        if self.state == state {
+            return;
+        }
+        self.state = state;
+        if let Some(monitor) = &self.monitor {
+            monitor.publish(self.status());
+        }
+    }
+
+    // Records every value read or written from now on, in the order it happened
+    pub fn record_io(&mut self) -> Recorder {
+        let recorder = Recorder::default();
+        self.attach_recorder(recorder.clone());
+        recorder
+    }
+
+    pub(crate) fn attach_recorder(&mut self, recorder: Recorder) {
+        self.recorder = Some(recorder);
+    }
+
+    // Returns false if the computer has been cancelled and should stop running
+    #[cfg(feature = "async")]
+    async fn wait_for_control(&mut self) -> bool {
+        let Some(mut control) = self.control.take() else {
+            return true;
+        };
+        let should_run = loop {
+            match control.current_command() {
+                ControlCommand::Run => break true,
+                ControlCommand::Pause => {
+                    // Reported for every pause received, so repeated requests are answered too
+                    self.set_state(ExecutionState::Paused);
+                    control.report_state(self.state(), false);
+                    if !control.command_changed().await {
+                        // Nobody is left to resume the computer, so carry on without a handle
+                        self.set_state(ExecutionState::Running);
+                        return true;
+                    }
+                }
+                ControlCommand::Cancel => {
+                    self.set_state(ExecutionState::Cancelled);
+                    break false;
+                }
+            }
+        };
+        if should_run {
+            let resumed = self.state == ExecutionState::Paused;
+            self.set_state(ExecutionState::Running);
+            if resumed {
+                control.report_state(self.state(), false);
+            }
+        }
+        self.control = Some(control);
+        should_run
+    }
+
+    #[cfg(feature = "async")]
+    fn report_state(&self, stopped: bool) {
+        if let Some(control) = &self.control {
+            control.report_state(self.state(), stopped);
+        }
+    }
+
+    #[cfg(feature = "async")]
+    fn state(&self) -> ComputerState {
+        ComputerState {
+            memory: self.memory.clone(),
+            status: self.status(),
+        }
     }
 
     pub fn set_memory(&mut self, address: usize, value: i64) {
@@ -71,40 +627,169 @@ impl IntcodeComputer {
         }
     }
 
-    async fn execute_instruction(&mut self, instruction: &Instruction) {
+    // Loads and stores made by the program on mapped addresses go to the device instead of
+    // memory, while get_memory and set_memory always access the backing memory
+    pub fn map_device(&mut self, addresses: Range<usize>, device: impl MemoryDevice + 'static) {
+        self.devices.map(addresses, Box::new(device));
+    }
+
+    pub fn unmap_device(&mut self, address: usize) -> Option<Box<dyn MemoryDevice>> {
+        self.devices.unmap(address)
+    }
+
+    fn load(&mut self, address: usize) -> i64 {
+        match self.devices.read(address) {
+            Some(value) => value,
+            None => self.get_memory(address),
+        }
+    }
+
+    fn store(&mut self, address: usize, value: i64) {
+        if !self.devices.write(address, value) {
+            self.set_memory(address, value);
+        }
+    }
+
+    // Returns false if the instruction was interrupted before it could complete
+    async fn execute_instruction(
+        &mut self,
+        instruction: &Instruction,
+    ) -> Result<bool, IntcodeError> {
+        match instruction {
+            Instruction::Input { dst } => {
+                let read_data = if let Some(default_input) = self.default_input {
+                    let input = self.input.as_mut().expect("Input channel not set");
+                    match input.try_read() {
+                        Some(read_data) => {
+                            self.idle_counter.reset();
+                            read_data
+                        }
+                        None => {
+                            self.idle_counter.increment();
+                            // Let other tasks run so a polling computer does not starve its peers
+                            #[cfg(feature = "async")]
+                            tokio::task::yield_now().await;
+                            default_input
+                        }
+                    }
+                } else {
+                    self.set_state(ExecutionState::WaitingForInput);
+                    let Some(read_data) = self.read_input().await? else {
+                        return Ok(false);
+                    };
+                    let Some(read_data) = read_data else {
+                        let instruction_pointer = self.instruction_pointer;
+                        let input = self.input.as_ref().expect("Input channel not set");
+                        return Err(if input.failed() {
+                            IntcodeError::InputFailed {
+                                instruction_pointer,
+                            }
+                        } else {
+                            IntcodeError::InputClosed {
+                                instruction_pointer,
+                            }
+                        });
+                    };
+                    self.set_state(ExecutionState::Running);
+                    read_data
+                };
+                self.finish_input(dst, read_data);
+            }
+            Instruction::Output { src } => {
+                // The operand is only loaded once, so an interrupted output that is retried does
+                // not read a mapped device again
+                let output_data = match self.pending_output.take() {
+                    Some((address, output_data)) if address == self.instruction_pointer => {
+                        output_data
+                    }
+                    _ => src.get_value(self),
+                };
+                let Some(sent) = self.write_output(output_data).await else {
+                    self.pending_output = Some((self.instruction_pointer, output_data));
+                    return Ok(false);
+                };
+                if !sent {
+                    return Err(IntcodeError::OutputClosed {
+                        instruction_pointer: self.instruction_pointer,
+                    });
+                }
+                self.finish_output(output_data);
+            }
+            _ => self.execute_pure(instruction),
+        }
+        Ok(true)
+    }
+
+    // Resolves to None if the computer was interrupted before a value arrived
+    #[cfg(feature = "async")]
+    async fn read_input(&mut self) -> Result<Option<Option<i64>>, IntcodeError> {
+        let input = self.input.as_mut().expect("Input channel not set");
+        let control = self.control.as_mut();
+        let read = async move {
+            match control {
+                // A sync input has already taken its value by the time the future exists, so the
+                // read has to win over an interrupt that arrived in the meantime
+                Some(control) => tokio::select! {
+                    biased;
+                    read_data = input.read_async() => Some(read_data),
+                    // Leave the instruction pointer in place so the read is retried later
+                    _ = control.interrupted() => None,
+                },
+                None => Some(input.read_async().await),
+            }
+        };
+        match self.input_timeout {
+            Some(timeout) => {
+                tokio::time::timeout(timeout, read)
+                    .await
+                    .map_err(|_| IntcodeError::InputTimeout {
+                        instruction_pointer: self.instruction_pointer,
+                        timeout,
+                    })
+            }
+            None => Ok(read.await),
+        }
+    }
+
+    #[cfg(not(feature = "async"))]
+    async fn read_input(&mut self) -> Result<Option<Option<i64>>, IntcodeError> {
+        let input = self.input.as_mut().expect("Input channel not set");
+        Ok(Some(input.read_async().await))
+    }
+
+    // Resolves to None if the computer was interrupted before the value was taken
+    #[cfg(feature = "async")]
+    async fn write_output(&mut self, output_data: i64) -> Option<bool> {
+        let output = self.output.as_mut().expect("Output channel not set");
+        match self.control.as_mut() {
+            Some(control) => tokio::select! {
+                biased;
+                sent = output.write_async(output_data) => Some(sent),
+                _ = control.interrupted() => None,
+            },
+            None => Some(output.write_async(output_data).await),
+        }
+    }
+
+    #[cfg(not(feature = "async"))]
+    async fn write_output(&mut self, output_data: i64) -> Option<bool> {
+        let output = self.output.as_mut().expect("Output channel not set");
+        Some(output.write_async(output_data).await)
+    }
+
+    // Executes every instruction apart from input, output and halt
+    fn execute_pure(&mut self, instruction: &Instruction) {
         match instruction {
             Instruction::Add { lhs, rhs, dst } => {
                 let result = lhs.get_value(self) + rhs.get_value(self);
-                self.set_memory(dst.get_dst_address(self.relative_base), result);
+                self.store(dst.get_dst_address(self.relative_base), result);
                 self.move_instruction_pointer(4);
             }
             Instruction::Multiply { lhs, rhs, dst } => {
                 let result = lhs.get_value(self) * rhs.get_value(self);
-                self.set_memory(dst.get_dst_address(self.relative_base), result);
+                self.store(dst.get_dst_address(self.relative_base), result);
                 self.move_instruction_pointer(4);
             }
-            Instruction::Halt => (),
-            Instruction::Input { dst } => {
-                let read_data = self
-                    .input
-                    .as_mut()
-                    .expect("Input channel not set")
-                    .recv()
-                    .await
-                    .expect("Failed to read input");
-                self.set_memory(dst.get_dst_address(self.relative_base), read_data);
-                self.move_instruction_pointer(2);
-            }
-            Instruction::Output { src } => {
-                let output_data = src.get_value(self);
-                self.output
-                    .as_mut()
-                    .expect("Output channel not set")
-                    .send(output_data)
-                    .await
-                    .expect("Failed to send output");
-                self.move_instruction_pointer(2);
-            }
             Instruction::JumpIfTrue {
                 condition,
                 jump_address,
@@ -131,7 +816,7 @@ impl IntcodeComputer {
                 } else {
                     0
                 };
-                self.set_memory(dst.get_dst_address(self.relative_base), result);
+                self.store(dst.get_dst_address(self.relative_base), result);
                 self.move_instruction_pointer(4);
             }
             Instruction::Equals { lhs, rhs, dst } => {
@@ -140,16 +825,33 @@ impl IntcodeComputer {
                 } else {
                     0
                 };
-                self.set_memory(dst.get_dst_address(self.relative_base), result);
+                self.store(dst.get_dst_address(self.relative_base), result);
                 self.move_instruction_pointer(4);
             }
             Instruction::RelativeBaseOffset { offset } => {
                 self.relative_base += offset.get_value(self);
                 self.move_instruction_pointer(2);
             }
+            Instruction::Halt | Instruction::Input { .. } | Instruction::Output { .. } => (),
         }
     }
 
+    fn finish_input(&mut self, dst: &Parameter, read_data: i64) {
+        if let Some(recorder) = &self.recorder {
+            recorder.record(IoEvent::Input(read_data));
+        }
+        self.store(dst.get_dst_address(self.relative_base), read_data);
+        self.move_instruction_pointer(2);
+    }
+
+    fn finish_output(&mut self, output_data: i64) {
+        self.idle_counter.reset();
+        if let Some(recorder) = &self.recorder {
+            recorder.record(IoEvent::Output(output_data));
+        }
+        self.move_instruction_pointer(2);
+    }
+
     fn move_instruction_pointer(&mut self, offset: i64) {
         if offset > 0 {
             self.instruction_pointer += offset as usize;
@@ -166,12 +868,22 @@ impl IntcodeComputer {
         self.memory = snapshot;
     }
 
-    pub fn reset_instruction_pointer(&mut self) {
+    // The output was closed when the computer halted, so a computer that had one cannot be
+    // started again
+    pub fn reset_instruction_pointer(&mut self) -> Result<(), IntcodeError> {
+        if self.output_closed {
+            return Err(IntcodeError::OutputClosed {
+                instruction_pointer: self.instruction_pointer,
+            });
+        }
         self.instruction_pointer = 0;
+        self.pending_output = None;
+        self.set_state(ExecutionState::Running);
+        Ok(())
     }
 }
 
-enum Instruction {
+pub(crate) enum Instruction {
     Add {
         lhs: Parameter,
         rhs: Parameter,
@@ -213,6 +925,46 @@ enum Instruction {
 }
 
 impl Instruction {
+    // Unlike from_memory_address, this does not panic on invalid instructions
+    pub(crate) fn try_from_memory_address(memory: &[i64], address: usize) -> Option<Instruction> {
+        let value = *memory.get(address)?;
+        let parameter_count = Instruction::parameter_count(value % 100)?;
+        if address + parameter_count >= memory.len() {
+            return None;
+        }
+        let valid_modes = (0..parameter_count)
+            .all(|parameter_index| ParamtereMode::is_valid(value, parameter_index));
+        if valid_modes {
+            Some(Instruction::from_memory_address(memory, address))
+        } else {
+            None
+        }
+    }
+
+    fn parameter_count(opcode: i64) -> Option<usize> {
+        match opcode {
+            1 | 2 | 7 | 8 => Some(3),
+            5 | 6 => Some(2),
+            3 | 4 | 9 => Some(1),
+            99 => Some(0),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn length(&self) -> usize {
+        match self {
+            Instruction::Add { .. }
+            | Instruction::Multiply { .. }
+            | Instruction::LessThan { .. }
+            | Instruction::Equals { .. } => 4,
+            Instruction::JumpIfTrue { .. } | Instruction::JumpIfFalse { .. } => 3,
+            Instruction::Input { .. }
+            | Instruction::Output { .. }
+            | Instruction::RelativeBaseOffset { .. } => 2,
+            Instruction::Halt => 1,
+        }
+    }
+
     fn from_memory_address(memory: &[i64], address: usize) -> Instruction {
         let value = memory[address];
         let opcode = value % 100;
@@ -260,13 +1012,18 @@ impl Instruction {
     }
 }
 
-enum ParamtereMode {
+pub(crate) enum ParamtereMode {
     Position,
     Immediate,
     Relative,
 }
 
 impl ParamtereMode {
+    fn is_valid(value: i64, parameter_index: usize) -> bool {
+        let divisor = 10_i64.pow(parameter_index as u32 + 2);
+        (0..=2).contains(&((value / divisor) % 10))
+    }
+
     fn from_memory_value(value: i64, parameter_index: usize) -> Self {
         let divisor = 10_i64.pow(parameter_index as u32 + 2);
         match (value / divisor) % 10 {
@@ -278,9 +1035,9 @@ impl ParamtereMode {
     }
 }
 
-struct Parameter {
-    mode: ParamtereMode,
-    value: i64,
+pub(crate) struct Parameter {
+    pub(crate) mode: ParamtereMode,
+    pub(crate) value: i64,
 }
 
 impl Parameter {
@@ -291,12 +1048,12 @@ impl Parameter {
         }
     }
 
-    fn get_value(&self, computer: &IntcodeComputer) -> i64 {
+    fn get_value(&self, computer: &mut IntcodeComputer) -> i64 {
         match self.mode {
-            ParamtereMode::Position => computer.get_memory(self.value as usize),
+            ParamtereMode::Position => computer.load(self.value as usize),
             ParamtereMode::Immediate => self.value,
             ParamtereMode::Relative => {
-                computer.get_memory((self.value + computer.relative_base) as usize)
+                computer.load((self.value + computer.relative_base) as usize)
             }
         }
     }
@@ -310,17 +1067,54 @@ impl Parameter {
     }
 }
 
+pub fn run_program_with_inputs(program: &str, inputs: &[i64]) -> RunResult {
+    IntcodeComputer::new(program).run_with_inputs(inputs)
+}
+
+#[cfg(feature = "async")]
 pub async fn run_program(program: &str, input: &str) -> String {
-    let (string_input, computer_input) = StringComputerInput::new(input.to_string());
     let (mut string_output, computer_output) = StringComputerOutput::new();
+    let string_output_future = tokio::spawn(async move { string_output.run().await });
+    run_with_string_input(program, input, computer_output).await;
+    string_output_future.await.unwrap()
+}
+
+#[cfg(feature = "async")]
+pub async fn run_program_values(program: &str, input: &str) -> Vec<i64> {
+    let (mut vec_output, computer_output) = VecComputerOutput::new();
+    let vec_output_future = tokio::spawn(async move { vec_output.run().await });
+    run_with_string_input(program, input, computer_output).await;
+    vec_output_future.await.unwrap()
+}
+
+#[cfg(feature = "async")]
+async fn run_with_string_input(program: &str, input: &str, computer_output: ChannelSender) {
+    let (string_input, computer_input) = StringComputerInput::new(input.to_string());
     let mut computer = IntcodeComputer::new_with_io(program, computer_input, computer_output);
 
     let string_input_future = tokio::spawn(async move { string_input.run().await });
-    let string_output_future = tokio::spawn(async move { string_output.run().await });
 
-    computer.run().await;
-    drop(computer); // Drop computer to ensure that the output future completes
+    computer.run().await.unwrap();
 
     string_input_future.abort();
-    string_output_future.await.unwrap()
+}
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut context = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+        thread::park();
+    }
 }
@@ -0,0 +1,493 @@
+use std::{
+    collections::VecDeque,
+    fs::File,
+    future::{self, Future},
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+    pin::Pin,
+    sync::{mpsc, Arc, Mutex},
+};
+
+#[cfg(feature = "async")]
+use tokio::{
+    runtime::RuntimeFlavor,
+    sync::mpsc::{Receiver, Sender, UnboundedReceiver, UnboundedSender},
+};
+
+pub type IoFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+// Returns None when no value is available
+pub trait IntcodeInput {
+    fn read(&mut self) -> Option<i64>;
+
+    // Returns None instead of waiting when no value is queued. Inputs that cannot tell whether a
+    // read would wait never return a value here.
+    fn try_read(&mut self) -> Option<i64> {
+        None
+    }
+
+    // Non-blocking mode only reads through try_read, so it refuses inputs that do not implement it
+    fn supports_try_read(&self) -> bool {
+        false
+    }
+
+    // Tells a closed input apart from one that stopped because of an error
+    fn failed(&self) -> bool {
+        false
+    }
+}
+
+// Returns false when the value could not be written and the output should be considered closed
+pub trait IntcodeOutput {
+    fn write(&mut self, value: i64) -> bool;
+}
+
+// Resolves to None once the input is closed and no more values will arrive
+pub trait AsyncIntcodeInput {
+    fn read_async(&mut self) -> IoFuture<'_, Option<i64>>;
+
+    // Returns None instead of waiting when no value is queued
+    fn try_read(&mut self) -> Option<i64>;
+
+    fn supports_try_read(&self) -> bool {
+        true
+    }
+
+    // An input timeout can only interrupt reads that yield while they wait
+    fn supports_timeout(&self) -> bool {
+        true
+    }
+
+    fn failed(&self) -> bool {
+        false
+    }
+}
+
+pub trait AsyncIntcodeOutput {
+    fn write_async(&mut self, value: i64) -> IoFuture<'_, bool>;
+}
+
+impl<T: IntcodeInput> AsyncIntcodeInput for T {
+    fn read_async(&mut self) -> IoFuture<'_, Option<i64>> {
+        let value = match IntcodeInput::try_read(self) {
+            Some(value) => Some(value),
+            None => read_blocking(|| self.read()),
+        };
+        Box::pin(future::ready(value))
+    }
+
+    fn try_read(&mut self) -> Option<i64> {
+        IntcodeInput::try_read(self)
+    }
+
+    fn supports_try_read(&self) -> bool {
+        IntcodeInput::supports_try_read(self)
+    }
+
+    // The read has already blocked by the time the future exists
+    fn supports_timeout(&self) -> bool {
+        false
+    }
+
+    fn failed(&self) -> bool {
+        IntcodeInput::failed(self)
+    }
+}
+
+impl<T: IntcodeOutput> AsyncIntcodeOutput for T {
+    fn write_async(&mut self, value: i64) -> IoFuture<'_, bool> {
+        Box::pin(future::ready(self.write(value)))
+    }
+}
+
+// A sync input may block until its value arrives, so a multi-threaded runtime is told to move its
+// other tasks off this thread first. A current-thread runtime has nowhere to move them to, which
+// makes run_blocking the better fit there for inputs such as stdin or std channels.
+#[cfg(feature = "async")]
+fn read_blocking(read: impl FnOnce() -> Option<i64>) -> Option<i64> {
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(read)
+        }
+        _ => read(),
+    }
+}
+
+#[cfg(not(feature = "async"))]
+fn read_blocking(read: impl FnOnce() -> Option<i64>) -> Option<i64> {
+    read()
+}
+
+#[cfg(feature = "async")]
+impl AsyncIntcodeInput for Receiver<i64> {
+    fn read_async(&mut self) -> IoFuture<'_, Option<i64>> {
+        Box::pin(self.recv())
+    }
+
+    fn try_read(&mut self) -> Option<i64> {
+        self.try_recv().ok()
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncIntcodeOutput for Sender<i64> {
+    fn write_async(&mut self, value: i64) -> IoFuture<'_, bool> {
+        Box::pin(async move { self.send(value).await.is_ok() })
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncIntcodeInput for UnboundedReceiver<i64> {
+    fn read_async(&mut self) -> IoFuture<'_, Option<i64>> {
+        Box::pin(self.recv())
+    }
+
+    fn try_read(&mut self) -> Option<i64> {
+        self.try_recv().ok()
+    }
+}
+
+#[cfg(feature = "async")]
+impl IntcodeOutput for UnboundedSender<i64> {
+    fn write(&mut self, value: i64) -> bool {
+        self.send(value).is_ok()
+    }
+}
+
+impl IntcodeInput for mpsc::Receiver<i64> {
+    fn read(&mut self) -> Option<i64> {
+        self.recv().ok()
+    }
+
+    fn try_read(&mut self) -> Option<i64> {
+        self.try_recv().ok()
+    }
+
+    fn supports_try_read(&self) -> bool {
+        true
+    }
+}
+
+impl IntcodeOutput for mpsc::Sender<i64> {
+    fn write(&mut self, value: i64) -> bool {
+        self.send(value).is_ok()
+    }
+}
+
+impl IntcodeInput for VecDeque<i64> {
+    fn read(&mut self) -> Option<i64> {
+        self.pop_front()
+    }
+
+    fn try_read(&mut self) -> Option<i64> {
+        self.read()
+    }
+
+    fn supports_try_read(&self) -> bool {
+        true
+    }
+}
+
+impl IntcodeOutput for VecDeque<i64> {
+    fn write(&mut self, value: i64) -> bool {
+        self.push_back(value);
+        true
+    }
+}
+
+impl IntcodeInput for Vec<i64> {
+    fn read(&mut self) -> Option<i64> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.remove(0))
+        }
+    }
+
+    fn try_read(&mut self) -> Option<i64> {
+        self.read()
+    }
+
+    fn supports_try_read(&self) -> bool {
+        true
+    }
+}
+
+impl IntcodeOutput for Vec<i64> {
+    fn write(&mut self, value: i64) -> bool {
+        self.push(value);
+        true
+    }
+}
+
+// Lets the caller keep a handle to an input or output after handing it to a computer
+impl<T: IntcodeInput> IntcodeInput for Arc<Mutex<T>> {
+    fn read(&mut self) -> Option<i64> {
+        self.lock().unwrap().read()
+    }
+
+    fn try_read(&mut self) -> Option<i64> {
+        self.lock().unwrap().try_read()
+    }
+
+    fn supports_try_read(&self) -> bool {
+        self.lock().unwrap().supports_try_read()
+    }
+
+    fn failed(&self) -> bool {
+        self.lock().unwrap().failed()
+    }
+}
+
+impl<T: IntcodeOutput> IntcodeOutput for Arc<Mutex<T>> {
+    fn write(&mut self, value: i64) -> bool {
+        self.lock().unwrap().write(value)
+    }
+}
+
+pub struct IterInput<I> {
+    iter: I,
+}
+
+impl<I: Iterator<Item = i64>> IterInput<I> {
+    pub fn new(iter: impl IntoIterator<IntoIter = I>) -> Self {
+        IterInput {
+            iter: iter.into_iter(),
+        }
+    }
+}
+
+impl<I: Iterator<Item = i64>> IntcodeInput for IterInput<I> {
+    fn read(&mut self) -> Option<i64> {
+        self.iter.next()
+    }
+
+    fn try_read(&mut self) -> Option<i64> {
+        self.read()
+    }
+
+    fn supports_try_read(&self) -> bool {
+        true
+    }
+}
+
+pub struct FnInput<F> {
+    callback: F,
+}
+
+impl<F: FnMut() -> Option<i64>> FnInput<F> {
+    pub fn new(callback: F) -> Self {
+        FnInput { callback }
+    }
+}
+
+// The callback is expected to answer right away, so it is also asked in non-blocking mode
+impl<F: FnMut() -> Option<i64>> IntcodeInput for FnInput<F> {
+    fn read(&mut self) -> Option<i64> {
+        (self.callback)()
+    }
+
+    fn try_read(&mut self) -> Option<i64> {
+        self.read()
+    }
+
+    fn supports_try_read(&self) -> bool {
+        true
+    }
+}
+
+pub struct FnOutput<F> {
+    callback: F,
+}
+
+impl<F: FnMut(i64)> FnOutput<F> {
+    pub fn new(callback: F) -> Self {
+        FnOutput { callback }
+    }
+}
+
+impl<F: FnMut(i64)> IntcodeOutput for FnOutput<F> {
+    fn write(&mut self, value: i64) -> bool {
+        (self.callback)(value);
+        true
+    }
+}
+
+// Only asks the callback for a value when the computer executes an input instruction, passing it
+// every output written so far. The returned output has to be given to the same computer.
+pub struct OnDemandInput<F> {
+    outputs: Arc<Mutex<Vec<i64>>>,
+    callback: F,
+}
+
+impl<F: FnMut(&[i64]) -> Option<i64>> OnDemandInput<F> {
+    pub fn new(callback: F) -> (Self, Arc<Mutex<Vec<i64>>>) {
+        let outputs = Arc::new(Mutex::new(Vec::new()));
+        (
+            OnDemandInput {
+                outputs: outputs.clone(),
+                callback,
+            },
+            outputs,
+        )
+    }
+}
+
+impl<F: FnMut(&[i64]) -> Option<i64>> IntcodeInput for OnDemandInput<F> {
+    fn read(&mut self) -> Option<i64> {
+        let outputs = self.outputs.lock().unwrap();
+        (self.callback)(&outputs)
+    }
+
+    fn try_read(&mut self) -> Option<i64> {
+        self.read()
+    }
+
+    fn supports_try_read(&self) -> bool {
+        true
+    }
+}
+
+// Reads numbers separated by newlines or commas, or lines of text in ASCII mode
+pub struct ReaderInput<R> {
+    reader: R,
+    pending: VecDeque<i64>,
+    ascii: bool,
+    error: Option<io::Error>,
+}
+
+impl<R: BufRead> ReaderInput<R> {
+    pub fn new(reader: R) -> Self {
+        ReaderInput {
+            reader,
+            pending: VecDeque::new(),
+            ascii: false,
+            error: None,
+        }
+    }
+
+    pub fn ascii(mut self) -> Self {
+        self.ascii = true;
+        self
+    }
+
+    // The read or parse error that stopped the input, after which no more values are returned
+    pub fn error(&self) -> Option<&io::Error> {
+        self.error.as_ref()
+    }
+
+    // Returns false at the end of the input
+    fn read_line(&mut self) -> io::Result<bool> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Ok(false);
+        }
+        if self.ascii {
+            let line = line.strip_suffix('\n').unwrap_or(&line);
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            self.pending
+                .extend(line.bytes().map(i64::from).chain([b'\n' as i64]));
+            return Ok(true);
+        }
+        for value in line
+            .split(',')
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+        {
+            let value = value.parse::<i64>().map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid input value: {}", value),
+                )
+            })?;
+            self.pending.push_back(value);
+        }
+        Ok(true)
+    }
+}
+
+pub type FileInput = ReaderInput<BufReader<File>>;
+
+impl FileInput {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(ReaderInput::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+impl<R: BufRead> IntcodeInput for ReaderInput<R> {
+    fn read(&mut self) -> Option<i64> {
+        while self.pending.is_empty() && self.error.is_none() {
+            match self.read_line() {
+                Ok(true) => (),
+                Ok(false) => return None,
+                Err(error) => self.error = Some(error),
+            }
+        }
+        self.pending.pop_front()
+    }
+
+    fn failed(&self) -> bool {
+        self.error.is_some()
+    }
+}
+
+// Writes one number per line, or the characters themselves in ASCII mode
+pub struct WriterOutput<W> {
+    writer: W,
+    ascii: bool,
+    flush: bool,
+}
+
+impl<W: Write> WriterOutput<W> {
+    pub fn new(writer: W) -> Self {
+        WriterOutput {
+            writer,
+            ascii: false,
+            flush: false,
+        }
+    }
+
+    pub fn ascii(mut self) -> Self {
+        self.ascii = true;
+        self
+    }
+
+    pub fn flushing(mut self) -> Self {
+        self.flush = true;
+        self
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+pub type FileOutput = WriterOutput<BufWriter<File>>;
+
+impl FileOutput {
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(WriterOutput::new(BufWriter::new(File::create(path)?)).flushing())
+    }
+
+    pub fn append(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::options().append(true).create(true).open(path)?;
+        Ok(WriterOutput::new(BufWriter::new(file)).flushing())
+    }
+}
+
+impl<W: Write> IntcodeOutput for WriterOutput<W> {
+    fn write(&mut self, value: i64) -> bool {
+        self.writer
+            .write_all(output_text(value, self.ascii).as_bytes())
+            .is_ok()
+            && (!self.flush || self.writer.flush().is_ok())
+    }
+}
+
+// Anything outside of the ASCII range is still written as a number on its own line
+pub(crate) fn output_text(value: i64, ascii: bool) -> String {
+    match u8::try_from(value) {
+        Ok(byte) if ascii && byte.is_ascii() => (byte as char).to_string(),
+        _ => format!("{}\n", value),
+    }
+}
@@ -0,0 +1,88 @@
+use std::ops::Range;
+
+// Addresses passed to a device are offsets from the start of the range it is mapped to
+pub trait MemoryDevice: Send {
+    fn read(&mut self, offset: usize) -> i64;
+    fn write(&mut self, offset: usize, value: i64);
+}
+
+pub struct CallbackDevice<R, W> {
+    on_read: R,
+    on_write: W,
+}
+
+impl<R, W> CallbackDevice<R, W>
+where
+    R: FnMut(usize) -> i64 + Send,
+    W: FnMut(usize, i64) + Send,
+{
+    pub fn new(on_read: R, on_write: W) -> Self {
+        CallbackDevice { on_read, on_write }
+    }
+}
+
+impl<R, W> MemoryDevice for CallbackDevice<R, W>
+where
+    R: FnMut(usize) -> i64 + Send,
+    W: FnMut(usize, i64) + Send,
+{
+    fn read(&mut self, offset: usize) -> i64 {
+        (self.on_read)(offset)
+    }
+
+    fn write(&mut self, offset: usize, value: i64) {
+        (self.on_write)(offset, value)
+    }
+}
+
+struct MappedDevice {
+    addresses: Range<usize>,
+    device: Box<dyn MemoryDevice>,
+}
+
+#[derive(Default)]
+pub(crate) struct DeviceMap {
+    devices: Vec<MappedDevice>,
+}
+
+impl DeviceMap {
+    pub(crate) fn map(&mut self, addresses: Range<usize>, device: Box<dyn MemoryDevice>) {
+        if self.devices.iter().any(|mapped| {
+            mapped.addresses.start < addresses.end && addresses.start < mapped.addresses.end
+        }) {
+            panic!("Device range {:?} overlaps an existing mapping", addresses);
+        }
+        self.devices.push(MappedDevice { addresses, device });
+    }
+
+    pub(crate) fn unmap(&mut self, address: usize) -> Option<Box<dyn MemoryDevice>> {
+        let index = self
+            .devices
+            .iter()
+            .position(|mapped| mapped.addresses.contains(&address))?;
+        Some(self.devices.remove(index).device)
+    }
+
+    // Returns None for ordinary addresses that are backed by the memory vector
+    pub(crate) fn read(&mut self, address: usize) -> Option<i64> {
+        self.find(address)
+            .map(|mapped| mapped.device.read(address - mapped.addresses.start))
+    }
+
+    // Returns false for ordinary addresses that are backed by the memory vector
+    pub(crate) fn write(&mut self, address: usize, value: i64) -> bool {
+        match self.find(address) {
+            Some(mapped) => {
+                mapped.device.write(address - mapped.addresses.start, value);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn find(&mut self, address: usize) -> Option<&mut MappedDevice> {
+        self.devices
+            .iter_mut()
+            .find(|mapped| mapped.addresses.contains(&address))
+    }
+}
@@ -0,0 +1,112 @@
+use std::fmt;
+
+use crate::disassembler::decode;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryChange {
+    pub address: usize,
+    pub old: i64,
+    pub new: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangedRun {
+    pub start: usize,
+    pub old: Vec<i64>,
+    pub new: Vec<i64>,
+}
+
+impl ChangedRun {
+    pub fn end(&self) -> usize {
+        self.start + self.new.len()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnnotatedChange {
+    pub change: MemoryChange,
+    pub instruction: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryDiff {
+    changes: Vec<MemoryChange>,
+}
+
+impl MemoryDiff {
+    // Snapshots may differ in length since memory grows on writes, missing cells count as 0
+    pub fn between(before: &[i64], after: &[i64]) -> Self {
+        let length = before.len().max(after.len());
+        let changes = (0..length)
+            .filter_map(|address| {
+                let old = before.get(address).copied().unwrap_or(0);
+                let new = after.get(address).copied().unwrap_or(0);
+                (old != new).then_some(MemoryChange { address, old, new })
+            })
+            .collect();
+        MemoryDiff { changes }
+    }
+
+    pub fn changes(&self) -> &[MemoryChange] {
+        &self.changes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn runs(&self) -> Vec<ChangedRun> {
+        let mut runs: Vec<ChangedRun> = Vec::new();
+        for change in &self.changes {
+            match runs.last_mut() {
+                Some(run) if run.end() == change.address => {
+                    run.old.push(change.old);
+                    run.new.push(change.new);
+                }
+                _ => runs.push(ChangedRun {
+                    start: change.address,
+                    old: vec![change.old],
+                    new: vec![change.new],
+                }),
+            }
+        }
+        runs
+    }
+
+    // Labels each changed address with the instruction covering it when the given memory is walked
+    // from the start, usually the snapshot taken before the run so that the annotation shows the
+    // code that was overwritten
+    pub fn annotate(&self, memory: &[i64]) -> Vec<AnnotatedChange> {
+        let decoded = decode(memory);
+        self.changes
+            .iter()
+            .map(|change| {
+                let index = decoded.partition_point(|(start, _)| *start <= change.address);
+                let instruction = match index.checked_sub(1).map(|index| &decoded[index]) {
+                    Some((start, Some(instruction)))
+                        if change.address < start + instruction.length() =>
+                    {
+                        Some(instruction.to_string())
+                    }
+                    _ => None,
+                };
+                AnnotatedChange {
+                    change: *change,
+                    instruction,
+                }
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for MemoryDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for run in self.runs() {
+            writeln!(f, "{}..{}:", run.start, run.end())?;
+            for (offset, (old, new)) in run.old.iter().zip(&run.new).enumerate() {
+                writeln!(f, "  {}: {} -> {}", run.start + offset, old, new)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,276 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::Notify;
+
+use crate::{
+    intcode_computer::{IntcodeComputer, IntcodeError},
+    intcode_io::{IntcodeInput, IntcodeOutput},
+};
+
+pub const DEFAULT_NAT_ADDRESS: i64 = 255;
+
+// A computer is considered idle after this many consecutive reads from an empty queue
+const IDLE_THRESHOLD: u64 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Packet {
+    pub destination: i64,
+    pub x: i64,
+    pub y: i64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NatLog {
+    pub received: Vec<Packet>,
+    pub delivered: Vec<Packet>,
+}
+
+impl NatLog {
+    pub fn delivered_same_y_twice(&self) -> bool {
+        match self.delivered[..] {
+            [.., previous, last] => previous.y == last.y,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetworkError {
+    // Every computer is idle and the NAT has no packet to wake the network up with
+    Idle,
+    Computer { address: usize, error: IntcodeError },
+}
+
+impl fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetworkError::Idle => write!(f, "Network is idle and the NAT has no packet"),
+            NetworkError::Computer { address, error } => {
+                write!(f, "Computer {} failed: {}", address, error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for NetworkError {}
+
+type StopCondition = Box<dyn Fn(&NatLog) -> bool + Send + Sync>;
+
+pub struct Network {
+    program: String,
+    size: usize,
+    nat_address: i64,
+    stop_condition: StopCondition,
+}
+
+impl Network {
+    pub fn new(program: &str, size: usize) -> Self {
+        Network {
+            program: program.to_string(),
+            size,
+            nat_address: DEFAULT_NAT_ADDRESS,
+            stop_condition: Box::new(NatLog::delivered_same_y_twice),
+        }
+    }
+
+    pub fn with_nat_address(mut self, nat_address: i64) -> Self {
+        self.nat_address = nat_address;
+        self
+    }
+
+    // Checked every time the NAT has delivered a packet
+    pub fn with_stop_condition(
+        mut self,
+        stop_condition: impl Fn(&NatLog) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.stop_condition = Box::new(stop_condition);
+        self
+    }
+
+    // Runs until the stop condition holds, by default once the NAT has delivered the same Y value
+    // to address 0 twice in a row, or every computer has halted
+    pub async fn run(&self) -> Result<NatLog, NetworkError> {
+        let state = Arc::new(Mutex::new(NetworkState::new(self.size, self.nat_address)));
+        let changed = Arc::new(Notify::new());
+        let mut controls = Vec::new();
+        let mut computer_futures = Vec::new();
+        for address in 0..self.size {
+            let mut computer = IntcodeComputer::new_with_io(
+                &self.program,
+                NetworkInput {
+                    address,
+                    state: state.clone(),
+                    changed: changed.clone(),
+                },
+                NetworkOutput {
+                    address,
+                    buffer: Vec::new(),
+                    state: state.clone(),
+                },
+            );
+            computer.set_non_blocking_input(-1);
+            controls.push(computer.control());
+            let state = state.clone();
+            let changed = changed.clone();
+            computer_futures.push(tokio::spawn(async move {
+                let result = computer.run().await;
+                {
+                    let mut state = state.lock().unwrap();
+                    state.running -= 1;
+                    // A failed computer never goes idle, so the network is stopped instead
+                    state.failed |= result.is_err();
+                }
+                changed.notify_one();
+                result
+            }));
+        }
+
+        // Only woken up when a computer goes idle or stops, which are the only changes that can
+        // end the run or let the NAT act
+        let idle = loop {
+            {
+                let mut state = state.lock().unwrap();
+                if state.failed || state.running == 0 {
+                    break false;
+                }
+                if state.is_idle() {
+                    if !state.wake_up() {
+                        break true;
+                    }
+                    if (self.stop_condition)(&state.nat) {
+                        break false;
+                    }
+                }
+            }
+            changed.notified().await;
+        };
+
+        for control in &mut controls {
+            control.cancel().await;
+        }
+        for (address, computer_future) in computer_futures.into_iter().enumerate() {
+            if let Err(error) = computer_future.await.unwrap() {
+                return Err(NetworkError::Computer { address, error });
+            }
+        }
+        if idle {
+            return Err(NetworkError::Idle);
+        }
+        let state = state.lock().unwrap();
+        Ok(state.nat.clone())
+    }
+}
+
+// Queues and idle tracking share one lock so the NAT sees a consistent view of the network
+struct NetworkState {
+    queues: Vec<VecDeque<i64>>,
+    idle_reads: Vec<u64>,
+    nat_address: i64,
+    nat_packet: Option<Packet>,
+    nat: NatLog,
+    running: usize,
+    failed: bool,
+}
+
+impl NetworkState {
+    fn new(size: usize, nat_address: i64) -> Self {
+        NetworkState {
+            queues: (0..size)
+                .map(|address| VecDeque::from([address as i64]))
+                .collect(),
+            idle_reads: vec![0; size],
+            nat_address,
+            nat_packet: None,
+            nat: NatLog::default(),
+            running: size,
+            failed: false,
+        }
+    }
+
+    fn route(&mut self, packet: Packet) {
+        if packet.destination == self.nat_address {
+            self.nat.received.push(packet);
+            self.nat_packet = Some(packet);
+        } else if let Some(queue) = usize::try_from(packet.destination)
+            .ok()
+            .and_then(|destination| self.queues.get_mut(destination))
+        {
+            queue.push_back(packet.x);
+            queue.push_back(packet.y);
+        }
+    }
+
+    fn is_idle(&self) -> bool {
+        self.queues.iter().all(VecDeque::is_empty)
+            && self.idle_reads.iter().all(|reads| *reads >= IDLE_THRESHOLD)
+    }
+
+    // Returns false if the NAT has not received a packet it could deliver
+    fn wake_up(&mut self) -> bool {
+        let Some(packet) = self.nat_packet else {
+            return false;
+        };
+        let packet = Packet {
+            destination: 0,
+            ..packet
+        };
+        self.nat.delivered.push(packet);
+        self.route(packet);
+        self.idle_reads[0] = 0;
+        true
+    }
+}
+
+struct NetworkInput {
+    address: usize,
+    state: Arc<Mutex<NetworkState>>,
+    changed: Arc<Notify>,
+}
+
+impl IntcodeInput for NetworkInput {
+    fn read(&mut self) -> Option<i64> {
+        let mut state = self.state.lock().unwrap();
+        let value = state.queues[self.address].pop_front();
+        match value {
+            Some(_) => state.idle_reads[self.address] = 0,
+            None => {
+                state.idle_reads[self.address] += 1;
+                if state.idle_reads[self.address] == IDLE_THRESHOLD {
+                    self.changed.notify_one();
+                }
+            }
+        }
+        value
+    }
+
+    fn try_read(&mut self) -> Option<i64> {
+        self.read()
+    }
+
+    fn supports_try_read(&self) -> bool {
+        true
+    }
+}
+
+struct NetworkOutput {
+    address: usize,
+    buffer: Vec<i64>,
+    state: Arc<Mutex<NetworkState>>,
+}
+
+impl IntcodeOutput for NetworkOutput {
+    fn write(&mut self, value: i64) -> bool {
+        let mut state = self.state.lock().unwrap();
+        state.idle_reads[self.address] = 0;
+        self.buffer.push(value);
+        if let [destination, x, y] = self.buffer[..] {
+            state.route(Packet { destination, x, y });
+            self.buffer.clear();
+        }
+        true
+    }
+}
@@ -0,0 +1,165 @@
+use std::{
+    collections::VecDeque,
+    fmt, fs, io,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use crate::intcode_computer::{IntcodeComputer, IntcodeError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IoEvent {
+    Input(i64),
+    Output(i64),
+}
+
+// Stored as one event per line, either "in <value>" or "out <value>"
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Recording {
+    events: Vec<IoEvent>,
+}
+
+impl Recording {
+    pub fn events(&self) -> &[IoEvent] {
+        &self.events
+    }
+
+    pub fn inputs(&self) -> Vec<i64> {
+        self.events
+            .iter()
+            .filter_map(|event| match event {
+                IoEvent::Input(value) => Some(*value),
+                IoEvent::Output(_) => None,
+            })
+            .collect()
+    }
+
+    pub fn outputs(&self) -> Vec<i64> {
+        self.events
+            .iter()
+            .filter_map(|event| match event {
+                IoEvent::Input(_) => None,
+                IoEvent::Output(value) => Some(*value),
+            })
+            .collect()
+    }
+
+    pub fn to_replay_string(&self) -> String {
+        let mut result = String::new();
+        for event in &self.events {
+            match event {
+                IoEvent::Input(value) => result.push_str(&format!("in {}\n", value)),
+                IoEvent::Output(value) => result.push_str(&format!("out {}\n", value)),
+            }
+        }
+        result
+    }
+
+    pub fn from_replay_string(replay: &str) -> Result<Self, String> {
+        let events = replay
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let (kind, value) = line
+                    .trim()
+                    .split_once(' ')
+                    .ok_or_else(|| format!("Invalid replay line: {}", line))?;
+                let value = value
+                    .parse::<i64>()
+                    .map_err(|_| format!("Invalid replay value: {}", line))?;
+                match kind {
+                    "in" => Ok(IoEvent::Input(value)),
+                    "out" => Ok(IoEvent::Output(value)),
+                    _ => Err(format!("Invalid replay event: {}", line)),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Recording { events })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_replay_string())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Recording::from_replay_string(&fs::read_to_string(path)?)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Recorder(Arc<Mutex<Recording>>);
+
+impl Recorder {
+    pub fn recording(&self) -> Recording {
+        self.0.lock().unwrap().clone()
+    }
+
+    pub(crate) fn record(&self, event: IoEvent) {
+        self.0.lock().unwrap().events.push(event);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayMismatch {
+    pub index: usize,
+    pub expected: Option<IoEvent>,
+    pub actual: Option<IoEvent>,
+    pub error: Option<IntcodeError>,
+}
+
+impl fmt::Display for ReplayMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Replay diverged at event {}: expected {:?}, got {:?}",
+            self.index, self.expected, self.actual
+        )?;
+        if let Some(error) = self.error {
+            write!(f, " ({})", error)?;
+        }
+        Ok(())
+    }
+}
+
+// Feeds the recorded inputs back without a driver and returns what the computer did with them.
+// A computer that stops with an error, e.g. because it asks for more input than was recorded,
+// diverges at the event it could not complete. The recorded I/O never waits, so this runs
+// without an async runtime.
+pub fn replay(program: &str, recording: &Recording) -> Result<Recording, ReplayMismatch> {
+    let inputs = VecDeque::from(recording.inputs());
+    let mut computer = IntcodeComputer::new_with_io(program, inputs, Vec::new());
+    let recorder = computer.record_io();
+    let result = computer.run_blocking();
+    let actual = recorder.recording();
+    match result {
+        Ok(()) => Ok(actual),
+        Err(error) => {
+            let index = actual.events().len();
+            Err(ReplayMismatch {
+                index,
+                expected: recording.events().get(index).copied(),
+                actual: None,
+                error: Some(error),
+            })
+        }
+    }
+}
+
+// Checks that the replayed run reads and writes exactly the recorded values in the same order
+pub fn verify_replay(program: &str, recording: &Recording) -> Result<(), ReplayMismatch> {
+    let actual = replay(program, recording)?;
+    let expected = recording.events();
+    let actual = actual.events();
+    for index in 0..expected.len().max(actual.len()) {
+        if expected.get(index) != actual.get(index) {
+            return Err(ReplayMismatch {
+                index,
+                expected: expected.get(index).copied(),
+                actual: actual.get(index).copied(),
+                error: None,
+            });
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,173 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    time::Instant,
+};
+
+use crate::{
+    intcode_computer::{IntcodeComputer, IntcodeError, StepEvent},
+    topology::{BlockedNode, Deadlock},
+    transcript::{Transcript, TranscriptEntry},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ComputerId(usize);
+
+struct ScheduledComputer {
+    name: String,
+    computer: IntcodeComputer,
+    input: VecDeque<i64>,
+    targets: Vec<ComputerId>,
+}
+
+// Outputs are only collected for computers that are not connected to any other computer, the
+// values sent over connections are in the transcript in the order they were sent
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScheduleResult {
+    pub outputs: HashMap<String, Vec<i64>>,
+    pub transcript: Transcript,
+}
+
+impl ScheduleResult {
+    pub fn output(&self, name: &str) -> Option<&[i64]> {
+        self.outputs.get(name).map(Vec::as_slice)
+    }
+
+    pub fn values(&self, from: &str, to: &str) -> Vec<i64> {
+        self.transcript
+            .entries()
+            .iter()
+            .filter(|entry| entry.source == from && entry.destination == to)
+            .map(|entry| entry.value)
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScheduleError {
+    Deadlock(Deadlock),
+    Failed { name: String, error: IntcodeError },
+}
+
+impl fmt::Display for ScheduleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScheduleError::Deadlock(deadlock) => write!(f, "{}", deadlock),
+            ScheduleError::Failed { name, error } => write!(f, "{} failed: {}", name, error),
+        }
+    }
+}
+
+impl std::error::Error for ScheduleError {}
+
+// Runs every computer on the current thread without an async runtime. The computers take turns in
+// the order they were added and each one runs until it halts or waits for input that has not been
+// sent yet, so the interleaving only depends on the programs and is the same on every run.
+#[derive(Default)]
+pub struct Scheduler {
+    computers: Vec<ScheduledComputer>,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Scheduler::default()
+    }
+
+    pub fn add_computer(
+        &mut self,
+        name: &str,
+        computer: IntcodeComputer,
+        initial_inputs: &[i64],
+    ) -> ComputerId {
+        self.computers.push(ScheduledComputer {
+            name: name.to_string(),
+            computer,
+            input: initial_inputs.iter().copied().collect(),
+            targets: Vec::new(),
+        });
+        ComputerId(self.computers.len() - 1)
+    }
+
+    // A computer with several targets sends every output to each of them
+    pub fn connect(&mut self, from: ComputerId, to: ComputerId) {
+        self.computers[from.0].targets.push(to);
+    }
+
+    pub fn computer(&self, id: ComputerId) -> &IntcodeComputer {
+        &self.computers[id.0].computer
+    }
+
+    // Fails as soon as a computer fails, or when a whole round passes without any computer
+    // executing an instruction while some of them have not halted yet
+    pub fn run(&mut self) -> Result<ScheduleResult, ScheduleError> {
+        let start = Instant::now();
+        let mut result = ScheduleResult::default();
+        for scheduled in &self.computers {
+            if scheduled.targets.is_empty() {
+                result.outputs.insert(scheduled.name.clone(), Vec::new());
+            }
+        }
+        loop {
+            let mut progressed = false;
+            for index in 0..self.computers.len() {
+                loop {
+                    let scheduled = &mut self.computers[index];
+                    let instruction_count = scheduled.computer.instruction_count();
+                    let event = scheduled.computer.run_until_event(&mut scheduled.input);
+                    progressed |= scheduled.computer.instruction_count() != instruction_count;
+                    let value = match event {
+                        StepEvent::Output(value) => value,
+                        StepEvent::WaitingForInput | StepEvent::Halted => break,
+                        StepEvent::Failed(error) => {
+                            return Err(ScheduleError::Failed {
+                                name: scheduled.name.clone(),
+                                error,
+                            })
+                        }
+                    };
+                    if scheduled.targets.is_empty() {
+                        result.outputs.get_mut(&scheduled.name).unwrap().push(value);
+                        continue;
+                    }
+                    for target in scheduled.targets.clone() {
+                        result.transcript.push(TranscriptEntry {
+                            sequence: result.transcript.entries().len() as u64,
+                            timestamp: start.elapsed(),
+                            source: self.computers[index].name.clone(),
+                            destination: self.computers[target.0].name.clone(),
+                            value,
+                        });
+                        self.computers[target.0].input.push_back(value);
+                    }
+                }
+            }
+            if self
+                .computers
+                .iter()
+                .all(|scheduled| scheduled.computer.is_halted())
+            {
+                return Ok(result);
+            }
+            if !progressed {
+                return Err(ScheduleError::Deadlock(self.deadlock()));
+            }
+        }
+    }
+
+    fn deadlock(&self) -> Deadlock {
+        Deadlock {
+            nodes: self
+                .computers
+                .iter()
+                .map(|scheduled| {
+                    let status = scheduled.computer.status();
+                    BlockedNode {
+                        name: scheduled.name.clone(),
+                        instruction_pointer: status.instruction_pointer,
+                        state: status.state,
+                    }
+                })
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use std::{
+    io,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+use tokio::{
+    io::{AsyncWrite, BufReader},
+    net::{tcp::OwnedWriteHalf, TcpListener, TcpStream},
+};
+
+use crate::{
+    computer_io::{PromptedComputerInput, WriterComputerOutput},
+    intcode_computer::{IntcodeComputer, IntcodeError},
+};
+
+// Runs a fresh computer for every client, reading newline or comma-separated numbers (or text in
+// ASCII mode) from the connection and writing the outputs back to it
+pub struct IntcodeServer {
+    program: String,
+    ascii: bool,
+}
+
+impl IntcodeServer {
+    pub fn new(program: &str) -> Self {
+        IntcodeServer {
+            program: program.to_string(),
+            ascii: false,
+        }
+    }
+
+    pub fn ascii(mut self) -> Self {
+        self.ascii = true;
+        self
+    }
+
+    // Only binds to localhost, the computers are not meant to be reachable from other machines
+    pub async fn listen(&self, port: u16) -> io::Result<()> {
+        let listener = TcpListener::bind(("127.0.0.1", port)).await?;
+        self.serve(listener).await
+    }
+
+    pub async fn serve(&self, listener: TcpListener) -> io::Result<()> {
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let program = self.program.clone();
+            let ascii = self.ascii;
+            tokio::spawn(async move {
+                // A client going away before the program halts is not an error worth reporting
+                let _ = run_connection(&program, stream, ascii).await;
+            });
+        }
+    }
+}
+
+async fn run_connection(program: &str, stream: TcpStream, ascii: bool) -> Result<(), IntcodeError> {
+    let (reader, writer) = stream.into_split();
+    let writer = SharedWriter(Arc::new(Mutex::new(writer)));
+    // Invalid input is reported back over the connection, but there is no prompt to get in the
+    // way of scripted clients
+    let mut input =
+        PromptedComputerInput::from_reader(BufReader::new(reader), writer.clone()).with_prompt("");
+    let mut output = WriterComputerOutput::new(writer);
+    if ascii {
+        input = input.ascii();
+        output = output.ascii();
+    }
+    IntcodeComputer::new_with_io(program, input, output)
+        .run()
+        .await
+}
+
+// Lets the input and the output of a computer write to the same connection
+#[derive(Clone)]
+struct SharedWriter(Arc<Mutex<OwnedWriteHalf>>);
+
+impl AsyncWrite for SharedWriter {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut *self.0.lock().unwrap()).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut *self.0.lock().unwrap()).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut *self.0.lock().unwrap()).poll_shutdown(cx)
+    }
+}
@@ -0,0 +1,543 @@
+use std::{collections::HashMap, fmt};
+
+#[cfg(feature = "async")]
+use std::{
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+#[cfg(feature = "async")]
+use tokio::{
+    sync::{
+        mpsc::{Receiver, Sender},
+        Notify,
+    },
+    task::JoinHandle,
+};
+
+#[cfg(feature = "async")]
+use crate::{
+    channel::ChannelSender,
+    computer_io::{LoggingConnector, TeeConnector, VecComputerOutput},
+    intcode_computer::StatusMonitor,
+    intcode_io::{AsyncIntcodeInput, AsyncIntcodeOutput, IoFuture},
+};
+use crate::{
+    intcode_computer::{ExecutionState, IntcodeComputer, IntcodeError},
+    scheduler::{ScheduleError, Scheduler},
+    transcript::{Transcript, TranscriptEntry},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId(usize);
+
+struct Node {
+    name: String,
+    program: String,
+    initial_inputs: Vec<i64>,
+}
+
+struct Edge {
+    from: NodeId,
+    to: NodeId,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EdgeLog {
+    pub from: String,
+    pub to: String,
+    pub values: Vec<i64>,
+    pub transcript: Transcript,
+}
+
+// Outputs are only collected for nodes without an outgoing edge, everything else is in the logs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TopologyResult {
+    pub outputs: HashMap<String, Vec<i64>>,
+    pub edge_logs: Vec<EdgeLog>,
+}
+
+impl TopologyResult {
+    pub fn output(&self, node: &str) -> Option<&[i64]> {
+        self.outputs.get(node).map(Vec::as_slice)
+    }
+
+    pub fn edge_log(&self, from: &str, to: &str) -> Option<&[i64]> {
+        self.edge_logs
+            .iter()
+            .find(|log| log.from == from && log.to == to)
+            .map(|log| log.values.as_slice())
+    }
+
+    pub fn transcript(&self) -> Transcript {
+        Transcript::merge(self.edge_logs.iter().map(|log| &log.transcript))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockedNode {
+    pub name: String,
+    pub instruction_pointer: usize,
+    pub state: ExecutionState,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deadlock {
+    pub nodes: Vec<BlockedNode>,
+}
+
+impl fmt::Display for Deadlock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Deadlock:")?;
+        for node in &self.nodes {
+            write!(
+                f,
+                " {} ({:?} at {})",
+                node.name, node.state, node.instruction_pointer
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailedNode {
+    pub name: String,
+    pub error: IntcodeError,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TopologyError {
+    Deadlock(Deadlock),
+    Failed(Vec<FailedNode>),
+}
+
+impl From<Deadlock> for TopologyError {
+    fn from(deadlock: Deadlock) -> Self {
+        TopologyError::Deadlock(deadlock)
+    }
+}
+
+impl From<ScheduleError> for TopologyError {
+    fn from(error: ScheduleError) -> Self {
+        match error {
+            ScheduleError::Deadlock(deadlock) => TopologyError::Deadlock(deadlock),
+            ScheduleError::Failed { name, error } => {
+                TopologyError::Failed(vec![FailedNode { name, error }])
+            }
+        }
+    }
+}
+
+impl fmt::Display for TopologyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TopologyError::Deadlock(deadlock) => write!(f, "{}", deadlock),
+            TopologyError::Failed(nodes) => {
+                write!(f, "Failed:")?;
+                for node in nodes {
+                    write!(f, " {} ({})", node.name, node.error)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Topology {
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+}
+
+impl Topology {
+    pub fn new() -> Self {
+        Topology::default()
+    }
+
+    // Initial inputs are queued before any value arrives over an edge, which is how phase
+    // settings reach the amplifiers before their first signal
+    pub fn add_node(&mut self, name: &str, program: &str, initial_inputs: &[i64]) -> NodeId {
+        self.nodes.push(Node {
+            name: name.to_string(),
+            program: program.to_string(),
+            initial_inputs: initial_inputs.to_vec(),
+        });
+        NodeId(self.nodes.len() - 1)
+    }
+
+    // A node with several outgoing edges sends every output to each of them
+    pub fn connect(&mut self, from: NodeId, to: NodeId) {
+        self.edges.push(Edge { from, to });
+    }
+
+    // Runs the same topology on the current thread with the deterministic scheduler instead of a
+    // task per computer and connector
+    pub fn run_scheduled(&self) -> Result<TopologyResult, TopologyError> {
+        let mut scheduler = Scheduler::new();
+        let ids = self
+            .nodes
+            .iter()
+            .map(|node| {
+                scheduler.add_computer(
+                    &node.name,
+                    IntcodeComputer::new(&node.program),
+                    &node.initial_inputs,
+                )
+            })
+            .collect::<Vec<_>>();
+        for edge in &self.edges {
+            scheduler.connect(ids[edge.from.0], ids[edge.to.0]);
+        }
+        let schedule = scheduler.run()?;
+
+        let edge_logs = self
+            .edges
+            .iter()
+            .map(|edge| {
+                let from = self.nodes[edge.from.0].name.clone();
+                let to = self.nodes[edge.to.0].name.clone();
+                let mut transcript = Transcript::new();
+                for entry in schedule.transcript.entries() {
+                    if entry.source == from && entry.destination == to {
+                        transcript.push(TranscriptEntry {
+                            sequence: transcript.entries().len() as u64,
+                            ..entry.clone()
+                        });
+                    }
+                }
+                EdgeLog {
+                    from,
+                    to,
+                    values: transcript.values(),
+                    transcript,
+                }
+            })
+            .collect();
+        Ok(TopologyResult {
+            outputs: schedule.outputs,
+            edge_logs,
+        })
+    }
+}
+
+#[cfg(feature = "async")]
+impl Topology {
+    // Fails instead of hanging when every node that has not halted is waiting for input that
+    // nobody will send, and with the error of every node that stopped with one
+    pub async fn run(&self) -> Result<TopologyResult, TopologyError> {
+        let (input_senders, input_receivers): (Vec<_>, Vec<_>) = self
+            .nodes
+            .iter()
+            .map(|node| {
+                let (sender, receiver) =
+                    tokio::sync::mpsc::channel(node.initial_inputs.len().max(1));
+                for value in &node.initial_inputs {
+                    sender.try_send(*value).unwrap();
+                }
+                (sender, receiver)
+            })
+            .unzip();
+
+        let tracker = Arc::new(DeadlockTracker::new(
+            self.nodes
+                .iter()
+                .map(|node| node.initial_inputs.len())
+                .collect(),
+            (0..self.nodes.len())
+                .map(|index| {
+                    self.edges
+                        .iter()
+                        .filter(|edge| edge.to.0 == index)
+                        .map(|edge| edge.from.0)
+                        .collect()
+                })
+                .collect(),
+        ));
+        let start = Instant::now();
+        let mut connector_futures = Vec::new();
+        let mut output_futures = Vec::new();
+        let mut tee_futures = Vec::new();
+        let mut computer_futures = Vec::new();
+        let mut monitors = Vec::new();
+        for ((index, node), computer_input) in self.nodes.iter().enumerate().zip(input_receivers) {
+            let outgoing_edges = self
+                .edges
+                .iter()
+                .filter(|edge| edge.from.0 == index)
+                .collect::<Vec<_>>();
+            let targets = outgoing_edges.iter().map(|edge| edge.to.0).collect();
+            let computer_output = if outgoing_edges.is_empty() {
+                let (mut vec_output, sender) = VecComputerOutput::new();
+                let name = node.name.clone();
+                output_futures.push(tokio::spawn(async move { (name, vec_output.run().await) }));
+                sender
+            } else {
+                let mut edge_senders = Vec::new();
+                for edge in outgoing_edges {
+                    let (sender, receiver) = tokio::sync::mpsc::channel(1);
+                    let connector_future = self.spawn_connector(
+                        edge,
+                        input_senders[edge.to.0].clone(),
+                        receiver,
+                        start,
+                    );
+                    connector_futures.push(connector_future);
+                    edge_senders.push(sender);
+                }
+                if edge_senders.len() == 1 {
+                    edge_senders.pop().unwrap().into()
+                } else {
+                    let (sender, receiver) = tokio::sync::mpsc::channel(1);
+                    let mut tee = TeeConnector::new(edge_senders, receiver);
+                    tee_futures.push(tokio::spawn(async move { tee.run().await }));
+                    sender.into()
+                }
+            };
+            let computer_input = TrackedInput {
+                node: index,
+                input: computer_input,
+                tracker: tracker.clone(),
+            };
+            let computer_output = TrackedOutput {
+                targets,
+                output: computer_output,
+                tracker: tracker.clone(),
+            };
+            let mut computer =
+                IntcodeComputer::new_with_io(&node.program, computer_input, computer_output);
+            monitors.push(computer.status_monitor());
+            let tracker = tracker.clone();
+            computer_futures.push(tokio::spawn(async move {
+                let result = computer.run().await;
+                tracker.stopped(index);
+                result
+            }));
+        }
+        // Only the connectors may keep the inputs open, so feedback loops end once a node halts
+        drop(input_senders);
+
+        let finished = async {
+            let mut failed = Vec::new();
+            for (node, computer_future) in self.nodes.iter().zip(&mut computer_futures) {
+                if let Err(error) = computer_future.await.unwrap() {
+                    failed.push(FailedNode {
+                        name: node.name.clone(),
+                        error,
+                    });
+                }
+            }
+            failed
+        };
+        tokio::select! {
+            failed = finished => {
+                if !failed.is_empty() {
+                    return Err(TopologyError::Failed(failed));
+                }
+            }
+            deadlock = self.detect_deadlock(&monitors, &tracker) => {
+                let tasks = computer_futures.iter().map(JoinHandle::abort_handle)
+                    .chain(connector_futures.iter().map(JoinHandle::abort_handle))
+                    .chain(output_futures.iter().map(JoinHandle::abort_handle))
+                    .chain(tee_futures.iter().map(JoinHandle::abort_handle));
+                for task in tasks {
+                    task.abort();
+                }
+                return Err(deadlock.into());
+            }
+        }
+
+        let mut result = TopologyResult::default();
+        for connector_future in connector_futures {
+            result.edge_logs.push(connector_future.await.unwrap());
+        }
+        for output_future in output_futures {
+            let (name, output) = output_future.await.unwrap();
+            result.outputs.insert(name, output);
+        }
+        Ok(result)
+    }
+
+    async fn detect_deadlock(
+        &self,
+        monitors: &[StatusMonitor],
+        tracker: &DeadlockTracker,
+    ) -> Deadlock {
+        tracker.deadlocked.notified().await;
+        // Every node is blocked, so the status each one published last is up to date
+        Deadlock {
+            nodes: self
+                .nodes
+                .iter()
+                .zip(monitors)
+                .map(|(node, monitor)| {
+                    let status = monitor.get();
+                    BlockedNode {
+                        name: node.name.clone(),
+                        instruction_pointer: status.instruction_pointer,
+                        state: status.state,
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    fn spawn_connector(
+        &self,
+        edge: &Edge,
+        target: Sender<i64>,
+        source: Receiver<i64>,
+        start: Instant,
+    ) -> JoinHandle<EdgeLog> {
+        let from = self.nodes[edge.from.0].name.clone();
+        let to = self.nodes[edge.to.0].name.clone();
+        let mut connector = LoggingConnector::new(target, source)
+            .with_names(&from, &to)
+            .with_start(start);
+        tokio::spawn(async move {
+            let transcript = connector.run().await;
+            EdgeLog {
+                from,
+                to,
+                values: transcript.values(),
+                transcript,
+            }
+        })
+    }
+}
+
+#[cfg(feature = "async")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Activity {
+    Running,
+    Waiting,
+    Stopped,
+}
+
+// Follows every value from the output that sent it to the input that reads it, so a deadlock is
+// recognised the moment the last running node starts to wait rather than by sampling
+#[cfg(feature = "async")]
+struct DeadlockTracker {
+    nodes: Mutex<TrackedNodes>,
+    deadlocked: Notify,
+}
+
+#[cfg(feature = "async")]
+struct TrackedNodes {
+    activity: Vec<Activity>,
+    // Values sent to each node that it has not read yet, wherever they are on the way
+    pending: Vec<usize>,
+    producers: Vec<Vec<usize>>,
+}
+
+#[cfg(feature = "async")]
+impl DeadlockTracker {
+    fn new(pending: Vec<usize>, producers: Vec<Vec<usize>>) -> Self {
+        DeadlockTracker {
+            nodes: Mutex::new(TrackedNodes {
+                activity: vec![Activity::Running; pending.len()],
+                pending,
+                producers,
+            }),
+            deadlocked: Notify::new(),
+        }
+    }
+
+    fn update(&self, update: impl FnOnce(&mut TrackedNodes)) {
+        let mut nodes = self.nodes.lock().unwrap();
+        update(&mut nodes);
+        if nodes.is_deadlocked() {
+            self.deadlocked.notify_one();
+        }
+    }
+
+    fn sent(&self, targets: &[usize]) {
+        self.update(|nodes| {
+            for target in targets {
+                nodes.pending[*target] += 1;
+            }
+        });
+    }
+
+    fn waiting(&self, node: usize) {
+        self.update(|nodes| nodes.activity[node] = Activity::Waiting);
+    }
+
+    fn received(&self, node: usize, value: Option<i64>) {
+        self.update(|nodes| {
+            nodes.activity[node] = Activity::Running;
+            if value.is_some() {
+                nodes.pending[node] -= 1;
+            }
+        });
+    }
+
+    fn stopped(&self, node: usize) {
+        self.update(|nodes| nodes.activity[node] = Activity::Stopped);
+    }
+}
+
+#[cfg(feature = "async")]
+impl TrackedNodes {
+    // A waiting node whose producers have all stopped is about to find its input closed, so it
+    // only counts as deadlocked while one of them could still send something
+    fn is_deadlocked(&self) -> bool {
+        let mut blocked = false;
+        for (node, activity) in self.activity.iter().enumerate() {
+            match activity {
+                Activity::Running => return false,
+                Activity::Waiting if self.pending[node] > 0 => return false,
+                Activity::Waiting => {
+                    blocked |= self.producers[node]
+                        .iter()
+                        .any(|producer| self.activity[*producer] != Activity::Stopped)
+                }
+                Activity::Stopped => {}
+            }
+        }
+        blocked
+    }
+}
+
+#[cfg(feature = "async")]
+struct TrackedInput {
+    node: usize,
+    input: Receiver<i64>,
+    tracker: Arc<DeadlockTracker>,
+}
+
+#[cfg(feature = "async")]
+impl AsyncIntcodeInput for TrackedInput {
+    fn read_async(&mut self) -> IoFuture<'_, Option<i64>> {
+        Box::pin(async move {
+            self.tracker.waiting(self.node);
+            let value = self.input.recv().await;
+            self.tracker.received(self.node, value);
+            value
+        })
+    }
+
+    fn try_read(&mut self) -> Option<i64> {
+        let value = self.input.try_recv().ok();
+        if value.is_some() {
+            self.tracker.received(self.node, value);
+        }
+        value
+    }
+}
+
+#[cfg(feature = "async")]
+struct TrackedOutput {
+    targets: Vec<usize>,
+    output: ChannelSender,
+    tracker: Arc<DeadlockTracker>,
+}
+
+#[cfg(feature = "async")]
+impl AsyncIntcodeOutput for TrackedOutput {
+    // Counted before the value is handed over so it is never missing from both ends
+    fn write_async(&mut self, value: i64) -> IoFuture<'_, bool> {
+        self.tracker.sent(&self.targets);
+        self.output.write_async(value)
+    }
+}
@@ -0,0 +1,100 @@
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranscriptEntry {
+    pub sequence: u64,
+    pub timestamp: Duration,
+    pub source: String,
+    pub destination: String,
+    pub value: i64,
+}
+
+// Timestamps are relative to the start of the run, so transcripts of separate runs line up
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Transcript {
+    entries: Vec<TranscriptEntry>,
+}
+
+impl Transcript {
+    pub fn new() -> Self {
+        Transcript::default()
+    }
+
+    pub fn push(&mut self, entry: TranscriptEntry) {
+        self.entries.push(entry);
+    }
+
+    pub fn entries(&self) -> &[TranscriptEntry] {
+        &self.entries
+    }
+
+    pub fn values(&self) -> Vec<i64> {
+        self.entries.iter().map(|entry| entry.value).collect()
+    }
+
+    // Combines transcripts of several connectors into one ordered by time, with the entries
+    // renumbered in that order
+    pub fn merge<'a>(transcripts: impl IntoIterator<Item = &'a Transcript>) -> Self {
+        let mut entries = transcripts
+            .into_iter()
+            .flat_map(|transcript| transcript.entries.iter().cloned())
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|entry| entry.timestamp);
+        for (sequence, entry) in entries.iter_mut().enumerate() {
+            entry.sequence = sequence as u64;
+        }
+        Transcript { entries }
+    }
+
+    pub fn to_json_lines(&self) -> String {
+        let mut result = String::new();
+        for entry in &self.entries {
+            result.push_str(&format!(
+                "{{\"sequence\":{},\"timestamp_us\":{},\"source\":\"{}\",\"destination\":\"{}\",\"value\":{}}}\n",
+                entry.sequence,
+                entry.timestamp.as_micros(),
+                escape_json(&entry.source),
+                escape_json(&entry.destination),
+                entry.value
+            ));
+        }
+        result
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut result = String::from("sequence,timestamp_us,source,destination,value\n");
+        for entry in &self.entries {
+            result.push_str(&format!(
+                "{},{},{},{},{}\n",
+                entry.sequence,
+                entry.timestamp.as_micros(),
+                escape_csv(&entry.source),
+                escape_csv(&entry.destination),
+                entry.value
+            ));
+        }
+        result
+    }
+}
+
+fn escape_json(value: &str) -> String {
+    let mut result = String::new();
+    for c in value.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            c if c.is_control() => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+fn escape_csv(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
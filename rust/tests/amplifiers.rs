@@ -0,0 +1,55 @@
+use intcode::{
+    amplifiers::{
+        optimise_phases, run_amplifiers, score_all_phases, AmplifierError, AmplifierMode,
+    },
+    topology::TopologyError,
+};
+
+#[test]
+fn test_three_amplifiers() {
+    let best = optimise_phases(
+        "3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0",
+        3,
+        &[0, 1, 2, 3, 4],
+        AmplifierMode::Serial,
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(best.phases, vec![4, 3, 2]);
+    assert_eq!(best.output, 432);
+}
+
+#[test]
+fn test_score_all_phases() {
+    let scores = score_all_phases(
+        "3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0",
+        2,
+        &[1, 2, 3],
+        AmplifierMode::Serial,
+    )
+    .unwrap();
+    assert_eq!(scores.len(), 6);
+    assert_eq!(scores[0].phases, vec![1, 2]);
+    assert_eq!(scores[0].output, 12);
+}
+
+#[test]
+fn test_amplifier_errors() {
+    assert_eq!(
+        run_amplifiers("3,0,99", &[], AmplifierMode::Feedback),
+        Err(AmplifierError::NoPhases)
+    );
+    assert_eq!(
+        optimise_phases("3,0,99", 0, &[0, 1], AmplifierMode::Serial),
+        Ok(None)
+    );
+    assert_eq!(
+        run_amplifiers("3,0,99", &[0], AmplifierMode::Serial),
+        Err(AmplifierError::NoOutput)
+    );
+    let result = run_amplifiers("3,0,3,0,3,0,99", &[0, 1], AmplifierMode::Feedback);
+    assert!(matches!(
+        result,
+        Err(AmplifierError::Topology(TopologyError::Deadlock(_)))
+    ));
+}
@@ -0,0 +1,62 @@
+use intcode::{
+    computer_builder::ComputerBuilder,
+    intcode_computer::{run_program_with_inputs, Dialect, HaltReason, IntcodeError, StatusMonitor},
+    replay::Recorder,
+};
+
+#[test]
+fn test_builder_dialect() {
+    let mut computer = ComputerBuilder::parse("1,0,0,0,3,0,99")
+        .dialect(Dialect::Day02)
+        .build();
+    assert_eq!(
+        computer.run_blocking(),
+        Err(IntcodeError::UnsupportedInstruction {
+            instruction_pointer: 4,
+            value: 3,
+            dialect: Dialect::Day02
+        })
+    );
+    assert_eq!(computer.get_memory(0), 2);
+}
+
+#[test]
+fn test_builder_limit_and_observers() {
+    let recorder = Recorder::default();
+    let monitor = StatusMonitor::new();
+    let builder = ComputerBuilder::new(vec![3, 9, 4, 9, 1105, 1, 0, 99, 0, 0])
+        .instruction_limit(5)
+        .recorder(recorder.clone())
+        .status_monitor(monitor.clone());
+
+    let result = builder.build().run_with_inputs(&[4, 2]);
+
+    assert_eq!(result.halt_reason, HaltReason::Failed);
+    assert_eq!(
+        result.error,
+        Some(IntcodeError::InstructionLimitExceeded {
+            instruction_pointer: 4,
+            limit: 5
+        })
+    );
+    assert_eq!(recorder.recording().outputs(), vec![4, 2]);
+    assert_eq!(monitor.get().memory_size, 10);
+    let unlimited = run_program_with_inputs("3,9,4,9,1105,1,0,99,0,0", &[4, 2]);
+    assert_eq!(unlimited.halt_reason, HaltReason::InputExhausted);
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn test_builder_input_timeout_fails_run_blocking() {
+    let timeout = std::time::Duration::from_millis(10);
+    let (_sender, computer_input) = tokio::sync::mpsc::channel(1);
+    let mut computer = ComputerBuilder::parse("3,0,99")
+        .input_timeout(timeout)
+        .build_with_io(computer_input, Vec::new());
+
+    assert_eq!(
+        computer.run_blocking(),
+        Err(IntcodeError::TimeoutWithoutRuntime { timeout })
+    );
+    assert_eq!(computer.instruction_pointer(), 0);
+}
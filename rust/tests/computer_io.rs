@@ -0,0 +1,162 @@
+#![cfg(feature = "async")]
+
+use std::time::Duration;
+
+use tokio::io::AsyncReadExt;
+
+use intcode::{
+    channel::ChannelCapacity,
+    computer_io::{
+        AsciiComputerInput, AsciiComputerOutput, MergeConnector, MergeOrder, PromptedComputerInput,
+        StringComputerInput, StringComputerOutput, TeeConnector, TransformConnector,
+        VecComputerOutput,
+    },
+    intcode_computer::IntcodeComputer,
+};
+
+#[tokio::test]
+async fn test_ascii_io() {
+    let (ascii_input, computer_input) = AsciiComputerInput::new("hi".to_string());
+    let (mut ascii_output, computer_output) = AsciiComputerOutput::new();
+    let mut computer = IntcodeComputer::new_with_io(
+        "3,100,4,100,1008,100,10,101,1006,101,0,104,1000,99",
+        computer_input,
+        computer_output,
+    );
+    tokio::spawn(async move { ascii_input.run().await });
+    let output_future = tokio::spawn(async move { ascii_output.run().await });
+
+    computer.run().await.unwrap();
+
+    let output = output_future.await.unwrap();
+    assert_eq!(output.text, "hi\n");
+    assert_eq!(output.values, vec![1000]);
+}
+
+#[tokio::test]
+async fn test_prompted_input() {
+    let (prompt_output, mut prompts) = tokio::io::duplex(1024);
+    let input = PromptedComputerInput::from_reader(&b"oops\n1, 2\n3\n"[..], prompt_output);
+    let mut computer =
+        IntcodeComputer::new_with_io("3,0,4,0,3,0,4,0,3,0,4,0,99", input, Vec::new());
+    let recorder = computer.record_io();
+
+    computer.run().await.unwrap();
+    drop(computer);
+
+    let mut prompt_text = String::new();
+    prompts.read_to_string(&mut prompt_text).await.unwrap();
+    assert_eq!(prompt_text, "INPUT: Invalid input: oops\nINPUT: INPUT: ");
+    assert_eq!(recorder.recording().outputs(), vec![1, 2, 3]);
+}
+
+#[tokio::test]
+async fn test_prompted_ascii_input() {
+    let input = PromptedComputerInput::from_reader(&b"hi\n"[..], tokio::io::sink())
+        .with_prompt("> ")
+        .ascii();
+    let mut computer =
+        IntcodeComputer::new_with_io("3,0,4,0,3,0,4,0,3,0,4,0,99", input, Vec::new());
+    let recorder = computer.record_io();
+
+    computer.run().await.unwrap();
+
+    assert_eq!(recorder.recording().outputs(), vec![104, 105, 10]);
+}
+
+#[tokio::test]
+async fn test_unbounded_channels() {
+    let (string_input, computer_input) =
+        StringComputerInput::with_capacity("1\n2\n3".to_string(), ChannelCapacity::Unbounded);
+    let (mut string_output, computer_output) =
+        StringComputerOutput::with_capacity(ChannelCapacity::Unbounded);
+    // Nobody is reading yet, which would block a bounded channel after the first value
+    string_input.run().await;
+    let mut computer = IntcodeComputer::new_with_io(
+        "3,0,4,0,3,0,4,0,3,0,4,0,99",
+        computer_input,
+        computer_output,
+    );
+
+    computer.run().await.unwrap();
+
+    assert_eq!(string_output.run().await, "1\n2\n3");
+}
+
+#[tokio::test]
+async fn test_merge_and_transform_connectors() {
+    let (first_sender, first_receiver) = tokio::sync::mpsc::channel(4);
+    let (second_sender, second_receiver) = tokio::sync::mpsc::channel(4);
+    let (merged_sender, merged_receiver) = tokio::sync::mpsc::channel(1);
+    let (mut vec_output, output_sender) = VecComputerOutput::new();
+    let mut merge = MergeConnector::new(merged_sender, vec![first_receiver, second_receiver]);
+    let mut filter = TransformConnector::filter(output_sender, merged_receiver, |data| data != 0);
+    for data in [1, 3, 5] {
+        first_sender.send(data).await.unwrap();
+    }
+    for data in [2, 0] {
+        second_sender.send(data).await.unwrap();
+    }
+    drop((first_sender, second_sender));
+
+    tokio::spawn(async move { merge.run().await });
+    tokio::spawn(async move { filter.run().await });
+
+    assert_eq!(vec_output.run().await, vec![1, 2, 3, 5]);
+}
+
+#[tokio::test]
+async fn test_arrival_merge_does_not_wait_for_silent_producer() {
+    let (_silent_sender, silent_receiver) = tokio::sync::mpsc::channel(1);
+    let (busy_sender, busy_receiver) = tokio::sync::mpsc::channel(1);
+    let (merged_sender, mut merged_receiver) = tokio::sync::mpsc::channel(1);
+    let mut merge = MergeConnector::new(merged_sender, vec![silent_receiver, busy_receiver])
+        .with_order(MergeOrder::Arrival);
+    tokio::spawn(async move { merge.run().await });
+
+    busy_sender.send(1).await.unwrap();
+    busy_sender.send(2).await.unwrap();
+
+    assert_eq!(merged_receiver.recv().await, Some(1));
+    assert_eq!(merged_receiver.recv().await, Some(2));
+}
+
+#[tokio::test]
+async fn test_round_robin_merge_ignores_timing() {
+    let (slow_sender, slow_receiver) = tokio::sync::mpsc::channel(1);
+    let (fast_sender, fast_receiver) = tokio::sync::mpsc::channel(4);
+    let (merged_sender, merged_receiver) = tokio::sync::mpsc::channel(1);
+    let (mut vec_output, output_sender) = VecComputerOutput::new();
+    let mut merge = MergeConnector::new(merged_sender, vec![slow_receiver, fast_receiver]);
+    let mut relay = TransformConnector::map(output_sender, merged_receiver, |data| data);
+    tokio::spawn(async move { merge.run().await });
+    tokio::spawn(async move { relay.run().await });
+
+    // Every value of the fast producer is queued before the slow one sends anything
+    for data in [2, 4, 6] {
+        fast_sender.send(data).await.unwrap();
+    }
+    drop(fast_sender);
+    for data in [1, 3] {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        slow_sender.send(data).await.unwrap();
+    }
+    drop(slow_sender);
+
+    assert_eq!(vec_output.run().await, vec![1, 2, 3, 4, 6]);
+}
+
+#[tokio::test]
+async fn test_tee_does_not_wait_for_full_consumer() {
+    let (input_sender, input_receiver) = tokio::sync::mpsc::channel(2);
+    let (full_sender, _full_receiver) = tokio::sync::mpsc::channel(1);
+    let (open_sender, mut open_receiver) = tokio::sync::mpsc::channel(1);
+    let mut tee = TeeConnector::new(vec![full_sender, open_sender], input_receiver);
+    tokio::spawn(async move { tee.run().await });
+
+    input_sender.send(1).await.unwrap();
+    input_sender.send(2).await.unwrap();
+
+    assert_eq!(open_receiver.recv().await, Some(1));
+    assert_eq!(open_receiver.recv().await, Some(2));
+}
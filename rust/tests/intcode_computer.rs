@@ -0,0 +1,270 @@
+#![cfg(feature = "async")]
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use intcode::{
+    computer_io::StringComputerOutput,
+    intcode_computer::{ExecutionState, IntcodeComputer, IntcodeError},
+    intcode_io::{FnInput, FnOutput, OnDemandInput, ReaderInput},
+    memory_device::CallbackDevice,
+};
+
+#[tokio::test]
+async fn test_non_blocking_input() {
+    let mut computer = IntcodeComputer::new_with_io("3,20,3,21,3,22,99", vec![7], Vec::new());
+    computer.set_non_blocking_input(-1);
+    computer.run().await.unwrap();
+    assert_eq!(computer.get_memory(20), 7);
+    assert_eq!(computer.get_memory(21), -1);
+    assert_eq!(computer.get_memory(22), -1);
+    assert_eq!(computer.idle_counter().get(), 2);
+}
+
+#[tokio::test]
+async fn test_non_blocking_std_channel_input() {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    sender.send(7).unwrap();
+    let mut computer = IntcodeComputer::new_with_io("3,20,3,21,99", receiver, Vec::new());
+    computer.set_non_blocking_input(-1);
+
+    // The sender is still open, so a blocking read would wait forever for the second value
+    computer.run().await.unwrap();
+
+    assert_eq!(computer.get_memory(20), 7);
+    assert_eq!(computer.get_memory(21), -1);
+    drop(sender);
+}
+
+#[tokio::test]
+async fn test_non_blocking_callback_inputs() {
+    let mut values = vec![5].into_iter();
+    let input = FnInput::new(move || values.next());
+    let mut computer = IntcodeComputer::new_with_io("3,20,3,21,99", input, Vec::new());
+    computer.set_non_blocking_input(-1);
+    computer.run().await.unwrap();
+    assert_eq!(computer.get_memory(20), 5);
+    assert_eq!(computer.get_memory(21), -1);
+
+    let (input, outputs) = OnDemandInput::new(|outputs| Some(outputs.len() as i64 + 1));
+    let mut computer = IntcodeComputer::new_with_io("3,20,4,20,3,21,99", input, outputs);
+    computer.set_non_blocking_input(-1);
+    computer.run().await.unwrap();
+    assert_eq!(computer.get_memory(21), 2);
+}
+
+#[test]
+#[should_panic(expected = "Input does not support non-blocking reads")]
+fn test_non_blocking_reader_input_is_rejected() {
+    let mut computer =
+        IntcodeComputer::new_with_io("3,20,99", ReaderInput::new(&b"1\n"[..]), Vec::new());
+    computer.set_non_blocking_input(-1);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_blocking_input_does_not_stall_other_tasks() {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let mut computer = IntcodeComputer::new_with_io("3,20,99", receiver, Vec::new());
+    let computer_future = tokio::spawn(async move {
+        computer.run().await.unwrap();
+        computer
+    });
+    // Scheduled on the only worker, which the computer is blocking while it waits for input
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        sender.send(7).unwrap();
+    });
+
+    assert_eq!(computer_future.await.unwrap().get_memory(20), 7);
+}
+
+#[tokio::test]
+async fn test_memory_mapped_devices() {
+    let mut computer = IntcodeComputer::new("1,100,100,200,99");
+    let mut clock = 0;
+    computer.map_device(
+        100..101,
+        CallbackDevice::new(
+            move |_| {
+                clock += 1;
+                clock
+            },
+            |_, _| (),
+        ),
+    );
+    let framebuffer = Arc::new(Mutex::new(vec![0; 4]));
+    let device_framebuffer = framebuffer.clone();
+    computer.map_device(
+        200..204,
+        CallbackDevice::new(
+            |_| 0,
+            move |offset, value| device_framebuffer.lock().unwrap()[offset] = value,
+        ),
+    );
+
+    computer.run().await.unwrap();
+
+    assert_eq!(*framebuffer.lock().unwrap(), vec![3, 0, 0, 0]);
+    assert_eq!(computer.get_memory(200), 0);
+    assert_eq!(computer.status().memory_size, 5);
+}
+
+#[tokio::test]
+async fn test_interrupted_output_reads_device_once() {
+    let (_sender, computer_input) = tokio::sync::mpsc::channel(1);
+    let (output_sender, mut output_receiver) = tokio::sync::mpsc::channel(1);
+    let mut computer =
+        IntcodeComputer::new_with_io("104,0,4,100,99", computer_input, output_sender);
+    let mut reads = 0;
+    computer.map_device(
+        100..101,
+        CallbackDevice::new(
+            move |_| {
+                reads += 1;
+                reads
+            },
+            |_, _| (),
+        ),
+    );
+    let mut control = computer.control();
+    let computer_future = tokio::spawn(async move { computer.run().await });
+
+    // The second output blocks on the full channel until the pause interrupts it
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    control.pause().await.unwrap();
+    control.resume();
+
+    assert_eq!(output_receiver.recv().await, Some(0));
+    assert_eq!(output_receiver.recv().await, Some(1));
+    computer_future.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn test_halt_closes_output() {
+    let (mut string_output, computer_output) = StringComputerOutput::new();
+    let (_sender, computer_input) = tokio::sync::mpsc::channel(1);
+    let mut computer = IntcodeComputer::new_with_io("104,42,99", computer_input, computer_output);
+    let output_future = tokio::spawn(async move { string_output.run().await });
+
+    computer.run().await.unwrap();
+
+    assert!(computer.is_halted());
+    assert_eq!(output_future.await.unwrap(), "42");
+    assert_eq!(
+        computer.reset_instruction_pointer(),
+        Err(IntcodeError::OutputClosed {
+            instruction_pointer: 2
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_input_timeout() {
+    let (_sender, computer_input) = tokio::sync::mpsc::channel(1);
+    let mut computer = IntcodeComputer::new_with_io("4,0,3,0,99", computer_input, Vec::new());
+    computer.set_input_timeout(Duration::from_millis(10));
+
+    let result = computer.run().await;
+
+    assert_eq!(
+        result,
+        Err(IntcodeError::InputTimeout {
+            instruction_pointer: 2,
+            timeout: Duration::from_millis(10),
+        })
+    );
+    assert!(!computer.is_halted());
+}
+
+#[test]
+#[should_panic(expected = "Input does not support timeouts")]
+fn test_input_timeout_rejects_sync_input() {
+    let (_sender, computer_input) = std::sync::mpsc::channel();
+    let mut computer = IntcodeComputer::new_with_io("3,0,99", computer_input, Vec::new());
+    computer.set_input_timeout(Duration::from_millis(10));
+}
+
+#[tokio::test]
+async fn test_repeated_pause_and_cancel_after_halt() {
+    let (sender, receiver) = tokio::sync::mpsc::channel(1);
+    let (output_sender, mut output_receiver) = tokio::sync::mpsc::channel(1);
+    let mut computer = IntcodeComputer::new_with_io("3,5,4,5,99,0", receiver, output_sender);
+    let mut control = computer.control();
+    let computer_future = tokio::spawn(async move {
+        computer.run().await.unwrap();
+        computer
+    });
+
+    let paused_state = control.pause().await.unwrap();
+    assert_eq!(control.pause().await.unwrap(), paused_state);
+    control.resume();
+    sender.send(7).await.unwrap();
+    assert_eq!(output_receiver.recv().await, Some(7));
+
+    // The computer is kept alive after halting, so only the final report can answer these
+    let computer = computer_future.await.unwrap();
+    assert!(computer.is_halted());
+    let halted_state = control.pause().await.unwrap();
+    assert_eq!(halted_state.status.state, ExecutionState::Halted);
+    assert_eq!(control.cancel().await.unwrap(), halted_state);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_pause_during_sync_io_keeps_the_value() {
+    // Either branch of an unbiased select would be picked half of the time, so try a few times
+    for _ in 0..4 {
+        let mut reads = 0;
+        let input = FnInput::new(move || {
+            std::thread::sleep(Duration::from_millis(30));
+            reads += 1;
+            Some(reads)
+        });
+        let outputs = Arc::new(Mutex::new(Vec::new()));
+        let written = outputs.clone();
+        let output = FnOutput::new(move |value| {
+            std::thread::sleep(Duration::from_millis(30));
+            written.lock().unwrap().push(value);
+        });
+        let mut computer = IntcodeComputer::new_with_io("3,0,4,0,99", input, output);
+        let mut control = computer.control();
+        let computer_future = tokio::spawn(async move { computer.run().await });
+
+        // Pause once while the input is sleeping and once while the output is
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        control.pause().await;
+        control.resume();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        control.pause().await;
+        control.resume();
+
+        computer_future.await.unwrap().unwrap();
+        assert_eq!(*outputs.lock().unwrap(), vec![1]);
+    }
+}
+
+#[tokio::test]
+async fn test_pause_resume_and_cancel() {
+    let program =
+        "3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5";
+    let (sender, receiver) = tokio::sync::mpsc::channel(1);
+    let (output_sender, mut output_receiver) = tokio::sync::mpsc::channel(1);
+    let mut computer = IntcodeComputer::new_with_io(program, receiver, output_sender);
+    let mut control = computer.control();
+    let computer_future = tokio::spawn(async move { computer.run().await });
+
+    let paused_state = control.pause().await.unwrap();
+    assert_eq!(paused_state.status.instruction_pointer, 0);
+    assert_eq!(paused_state.status.state, ExecutionState::Paused);
+    control.resume();
+
+    sender.send(5).await.unwrap();
+    sender.send(0).await.unwrap();
+    assert_eq!(output_receiver.recv().await, Some(1));
+
+    let final_state = control.cancel().await.unwrap();
+    assert_eq!(final_state.status.instruction_pointer, 6);
+    assert_eq!(final_state.memory[26], 1);
+    computer_future.await.unwrap().unwrap();
+}
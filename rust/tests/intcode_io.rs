@@ -0,0 +1,99 @@
+#![cfg(feature = "async")]
+
+use std::{
+    fs,
+    sync::{Arc, Mutex},
+};
+
+use intcode::{
+    intcode_computer::{IntcodeComputer, IntcodeError},
+    intcode_io::{FileInput, FileOutput, ReaderInput, WriterOutput},
+};
+
+#[tokio::test]
+async fn test_reader_and_writer_io() {
+    let output = Arc::new(Mutex::new(WriterOutput::new(Vec::new())));
+    let mut computer = IntcodeComputer::new_with_io(
+        "3,9,8,9,10,9,4,9,99,-1,8",
+        ReaderInput::new("8\n".as_bytes()),
+        output.clone(),
+    );
+    computer.run().await.unwrap();
+    let output = Arc::into_inner(output).unwrap().into_inner().unwrap();
+    assert_eq!(String::from_utf8(output.into_inner()).unwrap(), "1\n");
+}
+
+#[tokio::test]
+async fn test_file_io() {
+    let input_path =
+        std::env::temp_dir().join(format!("intcode-io-file-io-{}.in", std::process::id()));
+    let output_path =
+        std::env::temp_dir().join(format!("intcode-io-file-io-{}.out", std::process::id()));
+    fs::write(&input_path, "7\n8,9\n").unwrap();
+    let mut computer = IntcodeComputer::new_with_io(
+        "3,0,4,0,3,0,4,0,3,0,4,0,99",
+        FileInput::open(&input_path).unwrap(),
+        FileOutput::create(&output_path).unwrap(),
+    );
+
+    computer.run().await.unwrap();
+
+    // Outputs are flushed as they are written, so they are on disk before the computer is gone
+    assert_eq!(fs::read_to_string(&output_path).unwrap(), "7\n8\n9\n");
+    fs::remove_file(input_path).unwrap();
+    fs::remove_file(output_path).unwrap();
+}
+
+#[tokio::test]
+async fn test_ascii_file_io() {
+    let input_path = std::env::temp_dir().join(format!(
+        "intcode-io-ascii-file-io-{}.in",
+        std::process::id()
+    ));
+    let output_path = std::env::temp_dir().join(format!(
+        "intcode-io-ascii-file-io-{}.out",
+        std::process::id()
+    ));
+    fs::write(&input_path, "hi\n").unwrap();
+    let mut computer = IntcodeComputer::new_with_io(
+        "3,100,4,100,1008,100,10,101,1006,101,0,104,1000,99",
+        FileInput::open(&input_path).unwrap().ascii(),
+        FileOutput::create(&output_path).unwrap().ascii(),
+    );
+
+    computer.run().await.unwrap();
+
+    assert_eq!(fs::read_to_string(&output_path).unwrap(), "hi\n1000\n");
+    fs::remove_file(input_path).unwrap();
+    fs::remove_file(output_path).unwrap();
+}
+
+#[tokio::test]
+async fn test_invalid_reader_input() {
+    let input = Arc::new(Mutex::new(ReaderInput::new("1,x\n2\n".as_bytes())));
+    let mut computer = IntcodeComputer::new_with_io("3,0,3,0,99", input.clone(), Vec::new());
+
+    let result = computer.run().await;
+
+    assert_eq!(
+        result,
+        Err(IntcodeError::InputFailed {
+            instruction_pointer: 2
+        })
+    );
+    let input = input.lock().unwrap();
+    assert_eq!(input.error().unwrap().to_string(), "Invalid input value: x");
+}
+
+#[tokio::test]
+async fn test_shared_vec_output() {
+    let program = "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99";
+    let output = Arc::new(Mutex::new(Vec::new()));
+    let mut computer = IntcodeComputer::new_with_io(program, Vec::new(), output.clone());
+    computer.run().await.unwrap();
+    let expected = program
+        .split(',')
+        .map(|value| value.parse::<i64>().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(*output.lock().unwrap(), expected);
+}
@@ -0,0 +1,32 @@
+use intcode::{intcode_computer::IntcodeComputer, memory_diff::MemoryDiff};
+
+#[test]
+fn test_memory_diff() {
+    let mut computer = IntcodeComputer::new("1,9,10,3,2,3,11,0,99,30,40,50");
+    let before = computer.get_memory_snapshot();
+    computer.run_blocking().unwrap();
+    let diff = MemoryDiff::between(&before, &computer.get_memory_snapshot());
+
+    let changes = diff.changes();
+    assert_eq!(changes.len(), 2);
+    assert_eq!(
+        (changes[0].address, changes[0].old, changes[0].new),
+        (0, 1, 3500)
+    );
+    assert_eq!(
+        (changes[1].address, changes[1].old, changes[1].new),
+        (3, 3, 70)
+    );
+    assert_eq!(diff.runs().len(), 2);
+
+    let annotated = diff.annotate(&before);
+    assert_eq!(
+        annotated[0].instruction.as_deref(),
+        Some("add [9], [10], [3]")
+    );
+    // Address 3 is the destination operand of that same instruction
+    assert_eq!(
+        annotated[1].instruction.as_deref(),
+        Some("add [9], [10], [3]")
+    );
+}
@@ -0,0 +1,51 @@
+#![cfg(feature = "async")]
+
+use intcode::network::{Network, NetworkError, Packet};
+
+// Computer 0 sends a single packet to the NAT, every computer then polls its queue forever
+const PROGRAM: &str = "3,100,1005,100,11,104,255,104,7,104,42,3,101,1105,1,11";
+
+#[tokio::test]
+async fn test_nat_wakes_up_idle_network() {
+    let nat = Network::new(PROGRAM, 3).run().await.unwrap();
+    let received = Packet {
+        destination: 255,
+        x: 7,
+        y: 42,
+    };
+    let delivered = Packet {
+        destination: 0,
+        ..received
+    };
+    assert_eq!(nat.received, vec![received]);
+    assert_eq!(nat.delivered, vec![delivered, delivered]);
+}
+
+#[tokio::test]
+async fn test_custom_nat_address() {
+    let program = PROGRAM.replace("104,255", "104,5");
+    let nat = Network::new(&program, 3)
+        .with_nat_address(5)
+        .run()
+        .await
+        .unwrap();
+    assert_eq!(nat.received.len(), 1);
+    assert_eq!(nat.delivered.len(), 2);
+}
+
+#[tokio::test]
+async fn test_custom_stop_condition() {
+    let nat = Network::new(PROGRAM, 3)
+        .with_stop_condition(|nat| !nat.delivered.is_empty())
+        .run()
+        .await
+        .unwrap();
+    assert_eq!(nat.delivered.len(), 1);
+}
+
+#[tokio::test]
+async fn test_idle_network_without_nat_packet() {
+    // Every computer polls its queue forever without sending anything
+    let result = Network::new("3,100,1105,1,0", 3).run().await;
+    assert_eq!(result, Err(NetworkError::Idle));
+}
@@ -0,0 +1,41 @@
+use intcode::{
+    intcode_computer::IntcodeError,
+    replay::{verify_replay, IoEvent, Recording, ReplayMismatch},
+};
+
+#[test]
+fn test_divergent_replay() {
+    // The program asks for a second input that was never recorded
+    let recording = Recording::from_replay_string("in 5\nout 5\n").unwrap();
+
+    let result = verify_replay("3,0,4,0,3,0,99", &recording);
+
+    assert_eq!(
+        result,
+        Err(ReplayMismatch {
+            index: 2,
+            expected: None,
+            actual: None,
+            error: Some(IntcodeError::InputClosed {
+                instruction_pointer: 4
+            }),
+        })
+    );
+}
+
+#[test]
+fn test_replay_with_different_output() {
+    let recording = Recording::from_replay_string("in 5\nout 6\n").unwrap();
+
+    let result = verify_replay("3,0,4,0,99", &recording);
+
+    assert_eq!(
+        result,
+        Err(ReplayMismatch {
+            index: 1,
+            expected: Some(IoEvent::Output(6)),
+            actual: Some(IoEvent::Output(5)),
+            error: None,
+        })
+    );
+}
@@ -0,0 +1,44 @@
+use std::fs;
+
+use intcode::intcode_computer::{
+    run_program_with_inputs, ExecutionState, HaltReason, IntcodeComputer, IntcodeError,
+};
+
+#[test]
+fn test_status_after_halt() {
+    let mut computer = IntcodeComputer::new("1,9,10,3,2,3,11,0,99,30,40,50");
+    computer.run_blocking().unwrap();
+    let status = computer.status();
+    assert_eq!(status.instruction_pointer, 8);
+    assert_eq!(status.relative_base, 0);
+    assert_eq!(status.state, ExecutionState::Halted);
+    assert_eq!(status.instruction_count, 3);
+    assert_eq!(status.memory_size, 12);
+}
+
+#[test]
+fn test_run_result() {
+    let result = run_program_with_inputs(
+        &fs::read_to_string("src/bin/day-09/input.txt").unwrap(),
+        &[1],
+    );
+    assert_eq!(result.outputs, vec![3380552333]);
+    assert_eq!(result.halt_reason, HaltReason::Halted);
+    assert_eq!(result.error, None);
+    assert!(result.instruction_count > 0);
+}
+
+#[test]
+fn test_run_result_input_exhausted() {
+    let result = run_program_with_inputs("3,9,4,9,3,9,4,9,99,0", &[7]);
+    assert_eq!(result.outputs, vec![7]);
+    assert_eq!(result.memory[9], 7);
+    assert_eq!(result.instruction_count, 2);
+    assert_eq!(result.halt_reason, HaltReason::InputExhausted);
+    assert_eq!(
+        result.error,
+        Some(IntcodeError::InputClosed {
+            instruction_pointer: 4
+        })
+    );
+}
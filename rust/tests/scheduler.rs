@@ -0,0 +1,88 @@
+use intcode::{
+    computer_builder::ComputerBuilder,
+    intcode_computer::{ExecutionState, IntcodeComputer, IntcodeError},
+    scheduler::{ScheduleError, Scheduler},
+    topology::{Topology, TopologyError},
+};
+
+#[test]
+fn test_scheduler_interleaving() {
+    let echo = "3,100,4,100,1005,100,0,99";
+    let mut scheduler = Scheduler::new();
+    let left = scheduler.add_computer("left", IntcodeComputer::new(echo), &[3, 2, 1, 0]);
+    let right = scheduler.add_computer("right", IntcodeComputer::new(echo), &[]);
+    let sink = scheduler.add_computer("sink", IntcodeComputer::new(echo), &[]);
+    scheduler.connect(left, right);
+    scheduler.connect(right, sink);
+
+    let result = scheduler.run().unwrap();
+
+    let order = result
+        .transcript
+        .entries()
+        .iter()
+        .map(|entry| (entry.source.as_str(), entry.value))
+        .collect::<Vec<_>>();
+    // Left runs until it has used up its input before right gets a turn
+    assert_eq!(
+        order,
+        vec![
+            ("left", 3),
+            ("left", 2),
+            ("left", 1),
+            ("left", 0),
+            ("right", 3),
+            ("right", 2),
+            ("right", 1),
+            ("right", 0)
+        ]
+    );
+    assert_eq!(result.output("sink"), Some(&[3, 2, 1, 0][..]));
+    assert!(scheduler.computer(sink).is_halted());
+}
+
+#[test]
+fn test_scheduler_deadlock() {
+    let program =
+        "3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5";
+    let mut topology = Topology::new();
+    let first = topology.add_node("first", program, &[5]);
+    let second = topology.add_node("second", program, &[6]);
+    topology.connect(first, second);
+    topology.connect(second, first);
+
+    let Err(TopologyError::Deadlock(deadlock)) = topology.run_scheduled() else {
+        panic!("Expected a deadlock");
+    };
+
+    assert!(deadlock
+        .nodes
+        .iter()
+        .all(|node| node.state == ExecutionState::WaitingForInput));
+}
+
+#[test]
+fn test_scheduler_instruction_limit() {
+    let echo = "3,100,4,100,1005,100,0,99";
+    let mut scheduler = Scheduler::new();
+    let source = scheduler.add_computer("source", IntcodeComputer::new(echo), &[1, 0]);
+    let limited = scheduler.add_computer(
+        "limited",
+        ComputerBuilder::parse(echo).instruction_limit(4).build(),
+        &[],
+    );
+    scheduler.connect(source, limited);
+
+    let result = scheduler.run();
+
+    assert_eq!(
+        result,
+        Err(ScheduleError::Failed {
+            name: "limited".to_string(),
+            error: IntcodeError::InstructionLimitExceeded {
+                instruction_pointer: 2,
+                limit: 4
+            },
+        })
+    );
+}
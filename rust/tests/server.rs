@@ -0,0 +1,43 @@
+#![cfg(feature = "async")]
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+use intcode::server::IntcodeServer;
+
+// Echoes every value it reads until it reads 0
+const ECHO: &str = "3,100,4,100,1005,100,0,99";
+
+async fn start(server: IntcodeServer) -> std::net::SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { server.serve(listener).await });
+    address
+}
+
+#[tokio::test]
+async fn test_number_connection() {
+    let address = start(IntcodeServer::new(ECHO)).await;
+    let mut stream = TcpStream::connect(address).await.unwrap();
+    stream.write_all(b"5\nx\n6,0\n").await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    assert_eq!(response, "5\nInvalid input: x\n6\n0\n");
+}
+
+#[tokio::test]
+async fn test_one_computer_per_connection() {
+    let address = start(IntcodeServer::new(ECHO).ascii()).await;
+    let mut first = TcpStream::connect(address).await.unwrap();
+    let mut second = TcpStream::connect(address).await.unwrap();
+    second.write_all(b"b\0\n").await.unwrap();
+    first.write_all(b"a\0\n").await.unwrap();
+    let mut first_response = String::new();
+    first.read_to_string(&mut first_response).await.unwrap();
+    let mut second_response = String::new();
+    second.read_to_string(&mut second_response).await.unwrap();
+    assert_eq!(first_response, "a\0");
+    assert_eq!(second_response, "b\0");
+}
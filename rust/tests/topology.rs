@@ -0,0 +1,148 @@
+#![cfg(feature = "async")]
+
+use intcode::{
+    intcode_computer::{ExecutionState, IntcodeError},
+    topology::{FailedNode, Topology, TopologyError},
+};
+
+#[tokio::test]
+async fn test_failed_node() {
+    let mut topology = Topology::new();
+    let source = topology.add_node("source", "104,1,99", &[]);
+    let sink = topology.add_node("sink", "3,0,3,0,99", &[]);
+    topology.connect(source, sink);
+
+    let result = topology.run().await;
+
+    assert_eq!(
+        result,
+        Err(TopologyError::Failed(vec![FailedNode {
+            name: "sink".to_string(),
+            error: IntcodeError::InputClosed {
+                instruction_pointer: 2
+            },
+        }]))
+    );
+}
+
+#[tokio::test]
+async fn test_fan_out_topology() {
+    let program = "3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0";
+    let mut topology = Topology::new();
+    let source = topology.add_node("source", program, &[1, 0]);
+    let left = topology.add_node("left", program, &[2]);
+    let right = topology.add_node("right", program, &[3]);
+    topology.connect(source, left);
+    topology.connect(source, right);
+
+    let result = topology.run().await.unwrap();
+
+    assert_eq!(result.edge_log("source", "left"), Some(&[1][..]));
+    assert_eq!(result.edge_log("source", "right"), Some(&[1][..]));
+    assert_eq!(result.output("left"), Some(&[12][..]));
+    assert_eq!(result.output("right"), Some(&[13][..]));
+}
+
+#[tokio::test]
+async fn test_feedback_loop_transcript() {
+    let program =
+        "3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5";
+    let mut topology = Topology::new();
+    let first = topology.add_node("first", program, &[5, 0]);
+    let second = topology.add_node("second", program, &[6]);
+    topology.connect(first, second);
+    topology.connect(second, first);
+
+    let transcript = topology.run().await.unwrap().transcript();
+
+    let first_lines = transcript.to_json_lines();
+    let first_line = first_lines.lines().next().unwrap();
+    assert!(first_line.starts_with("{\"sequence\":0,\"timestamp_us\":"));
+    assert!(first_line.ends_with(",\"source\":\"first\",\"destination\":\"second\",\"value\":1}"));
+    let csv = transcript.to_csv();
+    assert_eq!(
+        csv.lines().next(),
+        Some("sequence,timestamp_us,source,destination,value")
+    );
+    assert_eq!(csv.lines().count(), transcript.entries().len() + 1);
+    let sequences = transcript
+        .entries()
+        .iter()
+        .map(|entry| entry.sequence)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        sequences,
+        (0..transcript.entries().len() as u64).collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test]
+async fn test_deadlock_detection() {
+    let program =
+        "3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5";
+    let mut topology = Topology::new();
+    let first = topology.add_node("first", program, &[5]);
+    let second = topology.add_node("second", program, &[6]);
+    topology.connect(first, second);
+    topology.connect(second, first);
+
+    let Err(TopologyError::Deadlock(deadlock)) = topology.run().await else {
+        panic!("Expected a deadlock");
+    };
+
+    let instruction_pointers = deadlock
+        .nodes
+        .iter()
+        .map(|node| (node.name.as_str(), node.instruction_pointer, node.state))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        instruction_pointers,
+        vec![
+            ("first", 6, ExecutionState::WaitingForInput),
+            ("second", 6, ExecutionState::WaitingForInput)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_deadlock_detection_through_tee() {
+    let mut topology = Topology::new();
+    let source = topology.add_node("source", "3,0,4,0,3,0,99", &[1]);
+    let left = topology.add_node("left", "3,0,3,0,99", &[]);
+    let right = topology.add_node("right", "3,0,3,0,99", &[]);
+    topology.connect(source, left);
+    topology.connect(source, right);
+    topology.connect(left, source);
+
+    let Err(TopologyError::Deadlock(deadlock)) = topology.run().await else {
+        panic!("Expected a deadlock");
+    };
+
+    let instruction_pointers = deadlock
+        .nodes
+        .iter()
+        .map(|node| (node.name.as_str(), node.instruction_pointer))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        instruction_pointers,
+        vec![("source", 4), ("left", 2), ("right", 2)]
+    );
+}
+
+#[tokio::test]
+async fn test_scheduled_topology_matches_tasks() {
+    let program =
+        "3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5";
+    let mut topology = Topology::new();
+    let first = topology.add_node("first", program, &[5, 0]);
+    let second = topology.add_node("second", program, &[6]);
+    topology.connect(first, second);
+    topology.connect(second, first);
+
+    let scheduled = topology.run_scheduled().unwrap();
+    let spawned = topology.run().await.unwrap();
+
+    for (from, to) in [("first", "second"), ("second", "first")] {
+        assert_eq!(scheduled.edge_log(from, to), spawned.edge_log(from, to));
+    }
+}